@@ -0,0 +1,9 @@
+-- Secondary email addresses used by members when sending status updates
+CREATE TABLE MemberEmailAlias (
+    alias_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_member_email_alias_email ON MemberEmailAlias (LOWER(email));
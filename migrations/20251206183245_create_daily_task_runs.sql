@@ -0,0 +1,10 @@
+-- Bookkeeping for the daily task so that missed days can be backfilled
+CREATE TABLE DailyTaskRun (
+    run_id SERIAL PRIMARY KEY,
+    run_date DATE NOT NULL UNIQUE,
+    succeeded BOOLEAN NOT NULL DEFAULT FALSE,
+    error TEXT,
+    triggered_by INT REFERENCES Member(member_id) ON DELETE SET NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP
+);
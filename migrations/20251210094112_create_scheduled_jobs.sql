@@ -0,0 +1,40 @@
+-- Persisted state for jobs run by the scheduler
+CREATE TYPE job_status AS ENUM ('Idle', 'Running', 'Succeeded', 'Failed');
+
+CREATE TABLE ScheduledJob (
+    job_name TEXT PRIMARY KEY,
+    schedule TEXT NOT NULL,
+    status job_status NOT NULL DEFAULT 'Idle',
+    last_run_at TIMESTAMP,
+    last_success_at TIMESTAMP,
+    next_run_at TIMESTAMP,
+    last_error TEXT,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- One row per attempt, including retries
+CREATE TABLE ScheduledJobRun (
+    run_id SERIAL PRIMARY KEY,
+    job_name TEXT NOT NULL REFERENCES ScheduledJob(job_name) ON DELETE CASCADE,
+    attempt INT NOT NULL,
+    status job_status NOT NULL DEFAULT 'Running',
+    error TEXT,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX idx_scheduled_job_run_job_name ON ScheduledJobRun (job_name, started_at DESC);
+
+-- Attendance and status history are now generated by separate jobs, so daily runs
+-- are tracked per job. Existing runs covered both.
+ALTER TABLE DailyTaskRun ADD COLUMN job_name TEXT;
+
+INSERT INTO DailyTaskRun (job_name, run_date, succeeded, error, triggered_by, started_at, completed_at)
+SELECT 'status_history', run_date, succeeded, error, triggered_by, started_at, completed_at
+FROM DailyTaskRun;
+
+UPDATE DailyTaskRun SET job_name = 'attendance' WHERE job_name IS NULL;
+
+ALTER TABLE DailyTaskRun ALTER COLUMN job_name SET NOT NULL;
+ALTER TABLE DailyTaskRun DROP CONSTRAINT dailytaskrun_run_date_key;
+ALTER TABLE DailyTaskRun ADD CONSTRAINT dailytaskrun_job_name_run_date_key UNIQUE (job_name, run_date);
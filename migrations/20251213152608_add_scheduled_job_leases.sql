@@ -0,0 +1,5 @@
+-- Lease used to make sure only one instance executes each scheduled run
+ALTER TABLE ScheduledJob
+ADD COLUMN locked_by TEXT,
+ADD COLUMN locked_until TIMESTAMP,
+ADD COLUMN claimed_run_at TIMESTAMP;
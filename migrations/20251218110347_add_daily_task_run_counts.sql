@@ -0,0 +1,2 @@
+-- Number of rows created by each daily run
+ALTER TABLE DailyTaskRun ADD COLUMN rows_created INT NOT NULL DEFAULT 0;
@@ -0,0 +1,29 @@
+-- Lifecycle state of a member. Only active members are tracked by the daily jobs.
+CREATE TYPE membership_status AS ENUM ('Active', 'OnLeave', 'Alumni', 'Suspended');
+
+ALTER TABLE Member ADD COLUMN membership_status membership_status NOT NULL DEFAULT 'Active';
+
+-- History of status changes. Changes with a future effective date stay pending
+-- (applied_at IS NULL) until the lifecycle job applies them.
+CREATE TABLE MembershipStatusChange (
+    change_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    from_status membership_status NOT NULL,
+    to_status membership_status NOT NULL,
+    effective_date DATE NOT NULL,
+    reason TEXT,
+    changed_by INT REFERENCES Member(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    applied_at TIMESTAMP,
+    cancelled_at TIMESTAMP
+);
+
+CREATE INDEX idx_membership_status_change_member ON MembershipStatusChange (member_id, created_at DESC);
+
+-- Academic years for which `year` has already been promoted
+CREATE TABLE AcademicYearPromotion (
+    academic_year INT PRIMARY KEY,
+    promoted_members INT NOT NULL DEFAULT 0,
+    graduated_members INT NOT NULL DEFAULT 0,
+    promoted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,28 @@
+-- Store the admission batch (the calendar year in which the member's first academic year
+-- started) instead of the current year of study, which goes stale every academic year.
+-- Existing values are converted using the day academic years start on, as MM-DD, which
+-- the server sets in `root.academic_year_start` from ACADEMIC_YEAR_START before migrating.
+-- Defaults to July 1st when run without it.
+ALTER TABLE Member ADD COLUMN batch INT;
+
+UPDATE Member
+SET batch = EXTRACT(YEAR FROM CURRENT_DATE)::INT
+    - (TO_CHAR(CURRENT_DATE, 'MM-DD') < COALESCE(NULLIF(current_setting('root.academic_year_start', true), ''), '07-01'))::INT
+    - year + 1
+WHERE year IS NOT NULL;
+
+ALTER TABLE Member DROP COLUMN year;
+
+-- Breaks target a batch, or every member when batch is NULL.
+-- Each break's year is converted relative to the academic year the break started in.
+ALTER TABLE StatusBreaks ADD COLUMN batch INT;
+
+UPDATE StatusBreaks
+SET batch = EXTRACT(YEAR FROM start_date)::INT
+    - (TO_CHAR(start_date, 'MM-DD') < COALESCE(NULLIF(current_setting('root.academic_year_start', true), ''), '07-01'))::INT
+    - year + 1;
+
+ALTER TABLE StatusBreaks DROP COLUMN year;
+
+-- Years are derived from the batch now, so promotions only graduate final-year members
+ALTER TABLE AcademicYearPromotion DROP COLUMN promoted_members;
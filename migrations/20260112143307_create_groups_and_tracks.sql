@@ -0,0 +1,76 @@
+-- Groups and tracks used to be an unconstrained integer and free text on Member.
+-- `Group` is a reserved word, hence `MemberGroup`.
+CREATE TABLE MemberGroup (
+    group_id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_member_group_name ON MemberGroup (LOWER(name));
+
+CREATE TABLE Track (
+    track_id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_track_name ON Track (LOWER(name));
+
+CREATE TABLE GroupMentor (
+    group_id INT NOT NULL REFERENCES MemberGroup(group_id) ON DELETE CASCADE,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, member_id)
+);
+
+CREATE TABLE TrackMentor (
+    track_id INT NOT NULL REFERENCES Track(track_id) ON DELETE CASCADE,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    PRIMARY KEY (track_id, member_id)
+);
+
+-- Keep the existing group numbers as IDs
+INSERT INTO MemberGroup (group_id, name)
+SELECT DISTINCT group_id, 'Group ' || group_id
+FROM Member
+WHERE group_id IS NOT NULL;
+
+SELECT setval('membergroup_group_id_seq', COALESCE((SELECT MAX(group_id) FROM MemberGroup), 0) + 1, false);
+
+ALTER TABLE Member
+    ADD CONSTRAINT member_group_id_fkey FOREIGN KEY (group_id)
+    REFERENCES MemberGroup(group_id) ON DELETE SET NULL;
+
+-- Spellings of the same track ("Web", "web", "Web Dev", "WebDev") share a key made of the
+-- lowercased letters and digits without a trailing "dev"/"development". Each track is named
+-- after its most common spelling.
+CREATE FUNCTION pg_temp.track_key(track TEXT) RETURNS TEXT AS $$
+    SELECT COALESCE(
+        NULLIF(REGEXP_REPLACE(LOWER(REGEXP_REPLACE(track, '[^a-zA-Z0-9]', '', 'g')), 'dev(elopment)?$', ''), ''),
+        LOWER(REGEXP_REPLACE(track, '[^a-zA-Z0-9]', '', 'g'))
+    )
+$$ LANGUAGE SQL IMMUTABLE;
+
+INSERT INTO Track (name)
+SELECT DISTINCT ON (key) TRIM(track)
+FROM (
+    SELECT track, pg_temp.track_key(track) AS key, COUNT(*) AS uses
+    FROM Member
+    WHERE NULLIF(TRIM(track), '') IS NOT NULL
+    GROUP BY track
+) spellings
+WHERE key <> ''
+ORDER BY key, uses DESC, track;
+
+ALTER TABLE Member ADD COLUMN track_id INT REFERENCES Track(track_id) ON DELETE SET NULL;
+
+UPDATE Member m
+SET track_id = t.track_id
+FROM Track t
+WHERE pg_temp.track_key(m.track) = pg_temp.track_key(t.name);
+
+ALTER TABLE Member DROP COLUMN track;
+
+CREATE INDEX idx_member_group_id ON Member (group_id);
+CREATE INDEX idx_member_track_id ON Member (track_id);
@@ -0,0 +1,43 @@
+-- Replaces the single-owner Project table dropped in 20250929191135_drop_unnecessary_tables.sql
+CREATE TYPE project_status AS ENUM ('Proposed', 'Active', 'Completed', 'Archived');
+CREATE TYPE project_role AS ENUM ('Lead', 'Contributor', 'Mentor');
+
+CREATE TABLE Project (
+    project_id SERIAL PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    repository_url TEXT,
+    status project_status NOT NULL DEFAULT 'Active',
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_project_timestamp
+BEFORE UPDATE ON Project
+FOR EACH ROW
+EXECUTE FUNCTION update_timestamp();
+
+CREATE TABLE ProjectTrack (
+    project_id INT NOT NULL REFERENCES Project(project_id) ON DELETE CASCADE,
+    track_id INT NOT NULL REFERENCES Track(track_id) ON DELETE CASCADE,
+    PRIMARY KEY (project_id, track_id)
+);
+
+CREATE TABLE ProjectMember (
+    project_id INT NOT NULL REFERENCES Project(project_id) ON DELETE CASCADE,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    role project_role NOT NULL DEFAULT 'Contributor',
+    joined_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (project_id, member_id)
+);
+
+CREATE INDEX idx_project_member_member_id ON ProjectMember (member_id);
+
+-- Projects a status update was about
+CREATE TABLE StatusUpdateProject (
+    update_id INT NOT NULL REFERENCES StatusUpdateHistory(update_id) ON DELETE CASCADE,
+    project_id INT NOT NULL REFERENCES Project(project_id) ON DELETE CASCADE,
+    PRIMARY KEY (update_id, project_id)
+);
+
+CREATE INDEX idx_status_update_project_project_id ON StatusUpdateProject (project_id);
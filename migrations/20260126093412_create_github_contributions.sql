@@ -0,0 +1,11 @@
+-- Daily counts of each member's public activity on the organization's repositories,
+-- filled in by the github_contributions job
+CREATE TABLE GitHubContribution (
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    commits INT NOT NULL DEFAULT 0,
+    pull_requests INT NOT NULL DEFAULT 0,
+    reviews INT NOT NULL DEFAULT 0,
+    synced_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (member_id, date)
+);
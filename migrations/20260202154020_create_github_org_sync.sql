@@ -0,0 +1,30 @@
+-- GitHub teams whose members are assigned to a track and/or group by the org sync
+CREATE TABLE GitHubTeamMapping (
+    team_slug VARCHAR(255) PRIMARY KEY,
+    track_id INT REFERENCES Track(track_id) ON DELETE CASCADE,
+    group_id INT REFERENCES MemberGroup(group_id) ON DELETE CASCADE,
+    CHECK (track_id IS NOT NULL OR group_id IS NOT NULL)
+);
+
+-- One row per run of the github_org_sync job
+CREATE TABLE GitHubOrgSync (
+    sync_id SERIAL PRIMARY KEY,
+    org_members INT NOT NULL DEFAULT 0,
+    sessions_revoked INT NOT NULL DEFAULT 0,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP
+);
+
+CREATE TYPE org_sync_difference_kind AS ENUM ('LeftOrg', 'NotRegistered', 'TrackChanged', 'GroupChanged');
+
+-- Differences between Members and the GitHub org found by a sync, reported to admins
+CREATE TABLE GitHubOrgSyncDifference (
+    difference_id SERIAL PRIMARY KEY,
+    sync_id INT NOT NULL REFERENCES GitHubOrgSync(sync_id) ON DELETE CASCADE,
+    kind org_sync_difference_kind NOT NULL,
+    member_id INT REFERENCES Member(member_id) ON DELETE CASCADE,
+    github_user VARCHAR(255) NOT NULL,
+    detail TEXT
+);
+
+CREATE INDEX idx_github_org_sync_difference_sync_id ON GitHubOrgSyncDifference (sync_id);
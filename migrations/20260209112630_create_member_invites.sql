@@ -0,0 +1,13 @@
+-- One-time codes admins hand out so that a member created by hand can link their
+-- GitHub account on first login. Only a hash of the code is stored.
+CREATE TABLE MemberInvite (
+    invite_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_by INT REFERENCES Member(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP
+);
+
+CREATE INDEX idx_member_invite_member_id ON MemberInvite (member_id);
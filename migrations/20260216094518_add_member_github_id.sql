@@ -0,0 +1,4 @@
+-- GitHub's numeric user ID never changes, unlike the login stored in github_user
+ALTER TABLE Member ADD COLUMN github_id BIGINT;
+
+CREATE UNIQUE INDEX idx_member_github_id ON Member (github_id);
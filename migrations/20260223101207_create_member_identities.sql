@@ -0,0 +1,20 @@
+-- Accounts at external identity providers that members log in with. A member has at
+-- most one account per provider.
+CREATE TABLE MemberIdentity (
+    identity_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    provider VARCHAR(64) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    username VARCHAR(255),
+    email VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMP,
+    UNIQUE (provider, subject),
+    UNIQUE (member_id, provider)
+);
+
+-- GitHub accounts linked so far
+INSERT INTO MemberIdentity (member_id, provider, subject, username)
+SELECT member_id, 'github', github_id::TEXT, github_user
+FROM Member
+WHERE github_id IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Single-use tokens sent by email for passwordless login. Only a hash of the token is stored.
+CREATE TABLE LoginToken (
+    token_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP
+);
+
+CREATE INDEX idx_login_token_member_id ON LoginToken (member_id);
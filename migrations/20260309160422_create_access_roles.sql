@@ -0,0 +1,17 @@
+-- Roles that grant members permissions beyond their base role, optionally only for the
+-- members of one track or group. The permissions of each role are defined in code.
+CREATE TYPE access_role AS ENUM ('Mentor', 'TrackLead', 'AttendanceOfficer');
+
+CREATE TABLE MemberAccessRole (
+    assignment_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    role access_role NOT NULL,
+    track_id INT REFERENCES Track(track_id) ON DELETE CASCADE,
+    group_id INT REFERENCES MemberGroup(group_id) ON DELETE CASCADE,
+    assigned_by INT REFERENCES Member(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK (track_id IS NULL OR group_id IS NULL)
+);
+
+CREATE UNIQUE INDEX idx_member_access_role_unique
+ON MemberAccessRole (member_id, role, COALESCE(track_id, 0), COALESCE(group_id, 0));
@@ -0,0 +1,2 @@
+-- Sessions started by an admin to act as another member. Deleting the admin ends them.
+ALTER TABLE Sessions ADD COLUMN impersonator_id INT REFERENCES Member(member_id) ON DELETE CASCADE;
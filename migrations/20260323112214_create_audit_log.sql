@@ -0,0 +1,23 @@
+-- Who did what: every GraphQL mutation, login and API key use. Actors aren't foreign keys
+-- so entries outlive deleted members. Bots are logged with their negative member ID.
+CREATE TYPE audit_event AS ENUM ('Mutation', 'Login', 'LoginFailed', 'ApiKeyUsed', 'ApiKeyRejected');
+
+CREATE TABLE AuditLog (
+    audit_id BIGSERIAL PRIMARY KEY,
+    event audit_event NOT NULL,
+    actor_id INT,
+    actor_role role_type,
+    impersonator_id INT,
+    operation TEXT,
+    arguments JSONB,
+    targets JSONB,
+    before JSONB,
+    after JSONB,
+    error TEXT,
+    ip TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_created_at ON AuditLog (created_at);
+CREATE INDEX idx_audit_log_actor ON AuditLog (actor_id, created_at);
+CREATE INDEX idx_audit_log_targets ON AuditLog USING GIN (targets);
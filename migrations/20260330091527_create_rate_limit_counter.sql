@@ -0,0 +1,9 @@
+-- Request counts of the Postgres rate limit store, shared by all instances. Each key
+-- counts hits until expires_at, after which its window starts over.
+CREATE UNLOGGED TABLE RateLimitCounter (
+    key TEXT PRIMARY KEY,
+    count INT NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_rate_limit_counter_expires_at ON RateLimitCounter (expires_at);
@@ -0,0 +1,8 @@
+-- Operations allowed in production when GRAPHQL_ALLOWLIST=db, keyed by the SHA-256 hash
+-- of their query text
+CREATE TABLE PersistedOperation (
+    operation_hash TEXT PRIMARY KEY,
+    name TEXT,
+    query TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
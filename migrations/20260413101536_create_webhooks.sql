@@ -0,0 +1,33 @@
+-- Outgoing webhooks registered by admins, and a log of every delivery. Deliveries are
+-- retried until they succeed or run out of attempts.
+CREATE TYPE webhook_event AS ENUM ('MemberCreated', 'AttendanceMarked', 'StatusSent', 'StreakBroken');
+CREATE TYPE webhook_delivery_status AS ENUM ('Pending', 'Delivered', 'Failed');
+
+CREATE TABLE Webhook (
+    webhook_id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events webhook_event[] NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by INT REFERENCES Member(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE WebhookDelivery (
+    delivery_id BIGSERIAL PRIMARY KEY,
+    webhook_id INT NOT NULL REFERENCES Webhook(webhook_id) ON DELETE CASCADE,
+    event webhook_event NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'Pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT NOW(),
+    response_status INT,
+    error TEXT,
+    redelivery_of BIGINT REFERENCES WebhookDelivery(delivery_id) ON DELETE SET NULL,
+    delivered_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_delivery_due ON WebhookDelivery (next_attempt_at) WHERE status = 'Pending';
+CREATE INDEX idx_webhook_delivery_webhook ON WebhookDelivery (webhook_id, created_at);
@@ -0,0 +1,26 @@
+-- Notifications sent to members who missed status updates, and the channels members
+-- opted out of. Each notification is recorded once per channel it was sent through.
+CREATE TYPE notification_channel AS ENUM ('Email', 'Discord', 'Webhook');
+
+CREATE TABLE MissedUpdateOptOut (
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    channel notification_channel NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (member_id, channel)
+);
+
+CREATE TABLE MissedUpdateNotification (
+    notification_id SERIAL PRIMARY KEY,
+    member_id INT NOT NULL REFERENCES Member(member_id) ON DELETE CASCADE,
+    channel notification_channel NOT NULL,
+    threshold INT NOT NULL,
+    misses INT NOT NULL,
+    -- Date of the member's last update before the misses, NULL if they never sent one
+    last_sent_date DATE,
+    error TEXT,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_missed_update_notification_member ON MissedUpdateNotification (member_id, last_sent_date);
+
+ALTER TYPE webhook_event ADD VALUE 'UpdatesMissed';
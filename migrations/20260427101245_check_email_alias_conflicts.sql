@@ -0,0 +1,41 @@
+-- Status updates are matched on primary emails and aliases alike, so an address that is
+-- one member's alias and another's primary email would mark both of them.
+CREATE OR REPLACE FUNCTION check_alias_not_primary_email()
+RETURNS TRIGGER AS
+$$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM Member
+        WHERE LOWER(email) = LOWER(NEW.email) AND member_id <> NEW.member_id
+    ) THEN
+        RAISE EXCEPTION '% is the primary email of another member', NEW.email
+            USING ERRCODE = 'unique_violation';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_alias_not_primary_email
+BEFORE INSERT OR UPDATE OF email ON MemberEmailAlias
+FOR EACH ROW
+EXECUTE FUNCTION check_alias_not_primary_email();
+
+CREATE OR REPLACE FUNCTION check_primary_email_not_alias()
+RETURNS TRIGGER AS
+$$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM MemberEmailAlias
+        WHERE LOWER(email) = LOWER(NEW.email) AND member_id <> NEW.member_id
+    ) THEN
+        RAISE EXCEPTION '% is an email alias of another member', NEW.email
+            USING ERRCODE = 'unique_violation';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_primary_email_not_alias
+BEFORE INSERT OR UPDATE OF email ON Member
+FOR EACH ROW
+EXECUTE FUNCTION check_primary_email_not_alias();
@@ -0,0 +1,122 @@
+use std::sync::{Arc, Mutex};
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextResolve, ResolveInfo,
+};
+use async_graphql::parser::types::{ExecutableDocument, Field};
+use async_graphql::{Name, ServerResult, Value, Variables};
+use serde_json::Map;
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::audit::{redact, targets, AuditService};
+use crate::auth::AuthContext;
+use crate::client_ip::ClientIp;
+use crate::models::audit::AuditEvent;
+
+/// Records every root mutation field in the audit log, including failed ones, with its
+/// arguments and snapshots of the rows its ID arguments point to.
+pub struct AuditLog;
+
+impl ExtensionFactory for AuditLog {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuditLogExtension::default())
+    }
+}
+
+#[derive(Default)]
+struct AuditLogExtension {
+    /// Variables of the request, to resolve arguments that use them
+    variables: Mutex<Variables>,
+}
+
+impl AuditLogExtension {
+    fn arguments(&self, field: &Field) -> serde_json::Value {
+        let variables = self.variables.lock().expect("Variables lock poisoned");
+
+        let arguments = field
+            .arguments
+            .iter()
+            .map(|(name, value)| {
+                let value = value
+                    .node
+                    .clone()
+                    .into_const_with(|variable: Name| {
+                        Ok::<_, std::convert::Infallible>(
+                            variables.get(&variable).cloned().unwrap_or(Value::Null),
+                        )
+                    })
+                    .unwrap_or(Value::Null);
+                (name.node.to_string(), value.into_json().unwrap_or_default())
+            })
+            .collect::<Map<_, _>>();
+
+        serde_json::Value::Object(arguments)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for AuditLogExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        *self.variables.lock().expect("Variables lock poisoned") = variables.clone();
+        next.run(ctx, query, variables).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.parent_type != "Mutation" {
+            return next.run(ctx, info).await;
+        }
+
+        let pool = ctx
+            .data_opt::<Arc<PgPool>>()
+            .expect("Pool must be in context.");
+
+        let operation = info.name.to_string();
+        let mut arguments = self.arguments(info.field);
+        let targets = targets(&arguments);
+        redact(&mut arguments);
+
+        let before = if targets.is_empty() {
+            None
+        } else {
+            AuditService::snapshot(pool, &targets)
+                .await
+                .inspect_err(|e| warn!("{}", e))
+                .ok()
+        };
+
+        let result = next.run(ctx, info).await;
+
+        let after = match before {
+            Some(_) => AuditService::snapshot(pool, &targets)
+                .await
+                .inspect_err(|e| warn!("{}", e))
+                .ok(),
+            None => None,
+        };
+
+        let mut entry =
+            AuditService::entry(ctx.data_opt::<AuthContext>(), ctx.data_opt::<ClientIp>());
+        entry.operation = Some(operation);
+        entry.arguments = Some(arguments);
+        entry.targets = (!targets.is_empty()).then_some(serde_json::Value::Object(targets));
+        entry.before = before;
+        entry.after = after;
+        entry.error = result.as_ref().err().map(|e| e.message.clone());
+
+        AuditService::try_record(pool, AuditEvent::Mutation, entry).await;
+
+        result
+    }
+}
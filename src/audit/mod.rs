@@ -0,0 +1,215 @@
+use serde_json::{Map, Value};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tracing::warn;
+
+use crate::auth::AuthContext;
+use crate::client_ip::ClientIp;
+use crate::models::audit::{AuditEvent, AuditLogEntry, AuditLogFilter, NewAuditEntry};
+
+pub mod extension;
+
+pub use extension::AuditLog;
+
+/// Argument names whose values are replaced before logging. Matched case-insensitively
+/// against any part of the name, e.g. `hmacSignature` or `inviteCode`.
+const SECRET_ARGUMENTS: [&str; 6] = ["password", "secret", "token", "signature", "code", "key"];
+
+/// Tables snapshotted for ID arguments, matched by suffix so `sourceMemberId` is a member.
+const TARGET_TABLES: [(&str, &str, &str); 7] = [
+    ("memberId", "Member", "member_id"),
+    ("projectId", "Project", "project_id"),
+    ("groupId", "MemberGroup", "group_id"),
+    ("trackId", "Track", "track_id"),
+    ("assignmentId", "MemberAccessRole", "assignment_id"),
+    ("identityId", "MemberIdentity", "identity_id"),
+    ("aliasId", "MemberEmailAlias", "alias_id"),
+];
+
+const REDACTED: &str = "[redacted]";
+
+pub struct AuditService;
+
+impl AuditService {
+    /// An entry for an action by the user of `auth`, from `ip`.
+    pub fn entry(auth: Option<&AuthContext>, ip: Option<&ClientIp>) -> NewAuditEntry {
+        let user = auth.and_then(|auth| auth.user());
+
+        NewAuditEntry {
+            actor_id: user.map(|user| user.member_id),
+            actor_role: user.map(|user| user.role),
+            impersonator_id: auth
+                .and_then(|auth| auth.impersonator())
+                .map(|impersonator| impersonator.member_id),
+            ip: ip.and_then(|ip| ip.to_string_opt()),
+            ..Default::default()
+        }
+    }
+
+    pub async fn record(
+        pool: &PgPool,
+        event: AuditEvent,
+        entry: NewAuditEntry,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO AuditLog (event, actor_id, actor_role, impersonator_id, operation,
+                arguments, targets, before, after, error, ip)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        )
+        .bind(event)
+        .bind(entry.actor_id)
+        .bind(entry.actor_role)
+        .bind(entry.impersonator_id)
+        .bind(entry.operation)
+        .bind(entry.arguments)
+        .bind(entry.targets)
+        .bind(entry.before)
+        .bind(entry.after)
+        .bind(entry.error)
+        .bind(entry.ip)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Record an entry, logging instead of failing if it can't be written, so that auditing
+    /// never breaks the request itself.
+    pub async fn try_record(pool: &PgPool, event: AuditEvent, entry: NewAuditEntry) {
+        if let Err(e) = Self::record(pool, event, entry).await {
+            warn!("{}", e);
+        }
+    }
+
+    /// Newest entries first
+    pub async fn search(
+        pool: &PgPool,
+        filter: AuditLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLogEntry>, String> {
+        let mut query: QueryBuilder<Postgres> =
+            QueryBuilder::new("SELECT * FROM AuditLog WHERE TRUE");
+
+        if let Some(event) = filter.event {
+            query.push(" AND event = ").push_bind(event);
+        }
+        if let Some(actor_id) = filter.actor_id {
+            query.push(" AND actor_id = ").push_bind(actor_id);
+        }
+        if let Some(operation) = filter.operation {
+            query.push(" AND operation = ").push_bind(operation);
+        }
+        if let Some(member_id) = filter.member_id {
+            query
+                .push(" AND (actor_id = ")
+                .push_bind(member_id)
+                .push(
+                    " OR EXISTS (
+                        SELECT 1 FROM jsonb_each(targets) t
+                        WHERE t.key ILIKE '%memberId' AND t.value = to_jsonb(",
+                )
+                .push_bind(member_id)
+                .push(")))");
+        }
+        if let Some(since) = filter.since {
+            query.push(" AND created_at >= ").push_bind(since);
+        }
+        if let Some(until) = filter.until {
+            query.push(" AND created_at < ").push_bind(until);
+        }
+
+        query
+            .push(" ORDER BY audit_id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        query
+            .build_query_as::<AuditLogEntry>()
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to search audit log: {}", e))
+    }
+
+    /// Rows of the tables in `TARGET_TABLES` that `targets` point to, keyed like `Member:3`.
+    /// Rows that don't exist are null.
+    pub async fn snapshot(pool: &PgPool, targets: &Map<String, Value>) -> Result<Value, String> {
+        let mut snapshot = Map::new();
+
+        for (key, value) in targets {
+            let key = key.to_lowercase();
+            let key = key.strip_suffix('s').unwrap_or(&key);
+            let Some((_, table, column)) = TARGET_TABLES
+                .iter()
+                .find(|(suffix, _, _)| key.ends_with(&suffix.to_lowercase()))
+            else {
+                continue;
+            };
+
+            let ids: Vec<i32> = match value {
+                Value::Array(values) => values.iter().filter_map(as_i32).collect(),
+                value => as_i32(value).into_iter().collect(),
+            };
+
+            for id in ids {
+                let row: Option<Value> = sqlx::query_scalar(&format!(
+                    "SELECT to_jsonb(t) FROM {table} t WHERE {column} = $1"
+                ))
+                .bind(id)
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| format!("Failed to snapshot {}: {}", table, e))?;
+
+                snapshot.insert(format!("{}:{}", table, id), row.unwrap_or(Value::Null));
+            }
+        }
+
+        Ok(Value::Object(snapshot))
+    }
+}
+
+fn as_i32(value: &Value) -> Option<i32> {
+    value.as_i64().and_then(|id| i32::try_from(id).ok())
+}
+
+/// Replace the values of secret-looking keys, at any depth.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_ARGUMENTS.iter().any(|secret| key.contains(secret)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// ID arguments, i.e. those named `...Id` or `...Ids`, flattened out of input objects.
+pub fn targets(arguments: &Value) -> Map<String, Value> {
+    let mut targets = Map::new();
+    collect_targets(arguments, &mut targets);
+    targets
+}
+
+fn collect_targets(value: &Value, targets: &mut Map<String, Value>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+
+    for (key, value) in map {
+        match value {
+            Value::Object(_) => collect_targets(value, targets),
+            Value::Number(_) | Value::Array(_) if key.ends_with("Id") || key.ends_with("Ids") => {
+                targets.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::models::auth::{ApiKey, Role};
-use crate::models::member::Member;
+use crate::models::member::{Member, MembershipStatus};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono_tz::Asia::Kolkata;
 use rand::Rng;
@@ -91,13 +91,15 @@ impl ApiKeyService {
                     name: key.name.clone(),
                     email: format!("bot-{}@internal.amfoss.in", key.api_key_id),
                     sex: None,
-                    year: None,
+                    batch: None,
                     hostel: None,
                     mac_address: None,
                     discord_id: None,
                     group_id: None,
-                    track: None,
+                    track_id: None,
                     github_user: None,
+                    github_id: None,
+                    membership_status: MembershipStatus::Active,
                     role: Role::Bot,
                     created_at: key.created_at,
                     updated_at: key.created_at,
@@ -1,61 +1,325 @@
-use crate::auth::oauth::GitHubOAuthService;
-use crate::models::auth::{GitHubUser, Role};
+use crate::auth::identity::{ExternalIdentity, IdentityProvider};
+use crate::auth::invite::InviteService;
+use crate::auth::oauth::GITHUB_PROVIDER;
+use crate::models::auth::Role;
 use crate::models::member::Member;
+use crate::models::webhook::WebhookEvent;
+use crate::webhooks::WebhookService;
 use chrono_tz::Asia::Kolkata;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+
+const MEMBER_COLUMNS: &str = "member_id, roll_no, name, email, sex, batch, hostel, mac_address,
+    discord_id, group_id, track_id, github_user, github_id, membership_status, role, created_at,
+    updated_at";
 
 pub struct AuthService;
 
 impl AuthService {
-    pub async fn handle_github_callback(pool: &PgPool, code: String) -> Result<Member, String> {
-        let oauth_service = GitHubOAuthService::new()
-            .map_err(|e| format!("Failed to initialize OAuth service: {}", e))?;
-
-        let github_user = oauth_service
-            .complete_oauth_flow(code)
+    /// Complete a login with `provider`. If `link_to` is given, the account is linked to that
+    /// member. Otherwise it is matched to a member by a linked identity, then by
+    /// `invite_code` if one was given, then by verified email, and linked to the member
+    /// found. If nothing matches, a new member is registered if the provider allows it.
+    pub async fn handle_callback(
+        pool: &PgPool,
+        provider: &dyn IdentityProvider,
+        code: String,
+        invite_code: Option<String>,
+        link_to: Option<&Member>,
+    ) -> Result<Member, String> {
+        let identity = provider
+            .authenticate(code)
             .await
             .map_err(|e| format!("OAuth flow failed: {}", e))?;
 
-        let existing_member = sqlx::query_as::<_, Member>(
-            "SELECT member_id, roll_no, name, email, sex, year, hostel, mac_address, discord_id,
-             group_id, track, github_user, role, created_at, updated_at
-             FROM Member
-             WHERE github_user = $1",
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+        if let Some(member) = link_to {
+            return Self::link_identity(&mut conn, member.member_id, &identity).await;
+        }
+
+        if let Some(member) = Self::find_by_identity(&mut conn, &identity).await? {
+            return Ok(member);
+        }
+
+        if let Some(invite_code) = invite_code {
+            // `link_by_invite` and `register_member` take their own connection, so release
+            // this one first in case the pool is exhausted
+            drop(conn);
+            return Self::link_by_invite(pool, &invite_code, &identity).await;
+        }
+
+        if identity.provider == GITHUB_PROVIDER {
+            if let Some(member_id) = Self::find_github_member(&mut conn, &identity).await? {
+                return Self::link_identity(&mut conn, member_id, &identity).await;
+            }
+        }
+
+        if let Some(member_id) = Self::find_by_email(&mut conn, &identity).await? {
+            return Self::link_identity(&mut conn, member_id, &identity).await;
+        }
+        drop(conn);
+
+        let email = provider
+            .registration_email(&identity)
+            .ok_or("No member matches this account, ask an admin for an invite")?;
+        Self::register_member(pool, identity, email).await
+    }
+
+    /// The member the account is linked to. Their username and email at the provider are
+    /// refreshed, and for GitHub so is `github_user`, in case the account was renamed.
+    async fn find_by_identity(
+        conn: &mut PgConnection,
+        identity: &ExternalIdentity,
+    ) -> Result<Option<Member>, String> {
+        let member_id: Option<i32> = sqlx::query_scalar(
+            "UPDATE MemberIdentity SET username = $3, email = $4, last_login_at = NOW()
+            WHERE provider = $1 AND subject = $2
+            RETURNING member_id",
         )
-        .bind(&github_user.github_username)
-        .fetch_optional(pool)
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .bind(&identity.username)
+        .bind(&identity.email)
+        .fetch_optional(&mut *conn)
         .await
-        .map_err(|e| format!("Failed to query member: {}", e))?;
+        .map_err(|e| format!("Failed to query identity: {}", e))?;
 
-        let member = if let Some(member) = existing_member {
-            // Member exists - return existing member
-            member
-        } else {
-            // Member doesn't exist - register new member
-            Self::register_member(pool, github_user).await?
+        let Some(member_id) = member_id else {
+            return Ok(None);
         };
 
+        if identity.provider == GITHUB_PROVIDER {
+            sqlx::query(
+                "UPDATE Member SET github_user = $2
+                WHERE member_id = $1 AND github_user IS DISTINCT FROM $2",
+            )
+            .bind(member_id)
+            .bind(&identity.username)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to update GitHub username: {}", e))?;
+        }
+
+        Self::fetch_member(conn, member_id).await.map(Some)
+    }
+
+    /// Members can have a GitHub ID without a linked identity, e.g. from the org sync, or
+    /// only a login from before IDs were stored.
+    async fn find_github_member(
+        conn: &mut PgConnection,
+        identity: &ExternalIdentity,
+    ) -> Result<Option<i32>, String> {
+        let github_id: i64 = identity
+            .subject
+            .parse()
+            .map_err(|_| "Invalid GitHub ID".to_string())?;
+
+        sqlx::query_scalar(
+            "SELECT member_id FROM Member
+            WHERE github_id = $1
+            OR (github_id IS NULL AND LOWER(github_user) = LOWER($2))
+            ORDER BY github_id IS NULL, member_id
+            LIMIT 1",
+        )
+        .bind(github_id)
+        .bind(&identity.username)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to query member: {}", e))
+    }
+
+    async fn link_by_invite(
+        pool: &PgPool,
+        invite_code: &str,
+        identity: &ExternalIdentity,
+    ) -> Result<Member, String> {
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let member_id = InviteService::redeem(&mut tx, invite_code)
+            .await?
+            .ok_or("Invite code is invalid or has expired")?;
+
+        let member = Self::link_identity(&mut tx, member_id, identity).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit invite: {}", e))?;
+
         Ok(member)
     }
 
-    async fn register_member(pool: &PgPool, github_user: GitHubUser) -> Result<Member, String> {
+    /// The member without an account at this provider whose email, or one of whose aliases,
+    /// is a verified address of the account. Several matches are ambiguous and need an
+    /// invite instead.
+    async fn find_by_email(
+        conn: &mut PgConnection,
+        identity: &ExternalIdentity,
+    ) -> Result<Option<i32>, String> {
+        let emails: Vec<String> = identity
+            .verified_emails
+            .iter()
+            .map(|email| email.to_lowercase())
+            .collect();
+
+        let member_ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT m.member_id FROM Member m
+            WHERE m.role <> 'Bot'
+            AND NOT EXISTS (
+                SELECT 1 FROM MemberIdentity i WHERE i.member_id = m.member_id AND i.provider = $2
+            )
+            AND ($2 <> $3 OR m.github_id IS NULL)
+            AND (
+                LOWER(m.email) = ANY($1)
+                OR EXISTS (
+                    SELECT 1 FROM MemberEmailAlias a
+                    WHERE a.member_id = m.member_id AND LOWER(a.email) = ANY($1)
+                )
+            )",
+        )
+        .bind(&emails)
+        .bind(&identity.provider)
+        .bind(GITHUB_PROVIDER)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to query member: {}", e))?;
+
+        match member_ids.as_slice() {
+            [] => Ok(None),
+            [member_id] => Ok(Some(*member_id)),
+            _ => Err(
+                "Several members match this account's emails, ask an admin for an invite"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Link the account to a member, who can have one account per provider. GitHub
+    /// accounts are also stored as the member's `github_id` and `github_user`.
+    async fn link_identity(
+        conn: &mut PgConnection,
+        member_id: i32,
+        identity: &ExternalIdentity,
+    ) -> Result<Member, String> {
+        let linked_to: Option<i32> = sqlx::query_scalar(
+            "SELECT member_id FROM MemberIdentity WHERE provider = $1 AND subject = $2",
+        )
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to query identity: {}", e))?;
+
+        match linked_to {
+            Some(id) if id != member_id => {
+                return Err(format!(
+                    "This {} account is linked to another member",
+                    identity.provider
+                ))
+            }
+            Some(_) => {}
+            None => {
+                sqlx::query(
+                    "INSERT INTO MemberIdentity
+                        (member_id, provider, subject, username, email, last_login_at)
+                    VALUES ($1, $2, $3, $4, $5, NOW())",
+                )
+                .bind(member_id)
+                .bind(&identity.provider)
+                .bind(&identity.subject)
+                .bind(&identity.username)
+                .bind(&identity.email)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::Database(e) if e.is_unique_violation() => format!(
+                        "This member already has a linked {} account",
+                        identity.provider
+                    ),
+                    e => format!("Failed to link identity: {}", e),
+                })?;
+            }
+        }
+
+        if identity.provider == GITHUB_PROVIDER {
+            let github_id: i64 = identity
+                .subject
+                .parse()
+                .map_err(|_| "Invalid GitHub ID".to_string())?;
+
+            sqlx::query("UPDATE Member SET github_id = $2, github_user = $3 WHERE member_id = $1")
+                .bind(member_id)
+                .bind(github_id)
+                .bind(&identity.username)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to link GitHub account: {}", e))?;
+        }
+
+        Self::fetch_member(conn, member_id).await
+    }
+
+    async fn fetch_member(conn: &mut PgConnection, member_id: i32) -> Result<Member, String> {
+        sqlx::query_as::<_, Member>(&format!(
+            "SELECT {} FROM Member WHERE member_id = $1",
+            MEMBER_COLUMNS
+        ))
+        .bind(member_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to query member: {}", e))
+    }
+
+    async fn register_member(
+        pool: &PgPool,
+        identity: ExternalIdentity,
+        email: String,
+    ) -> Result<Member, String> {
         let now = chrono::Utc::now().with_timezone(&Kolkata);
 
-        let member = sqlx::query_as::<_, Member>(
-            "INSERT INTO Member (name, email, github_user, role, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6)
-             RETURNING member_id, roll_no, name, email, sex, year, hostel, mac_address, discord_id,
-             group_id, track, github_user, role, created_at, updated_at",
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let member_id: i32 = sqlx::query_scalar(
+            "INSERT INTO Member (name, email, role, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING member_id",
         )
-        .bind(github_user.name)
-        .bind(github_user.email)
-        .bind(github_user.github_username)
+        .bind(&identity.name)
+        .bind(email)
         .bind(Role::Member)
         .bind(now)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| format!("Failed to register member: {}", e))?;
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                "A member with this email already exists. Log in as them and link this account, \
+                or ask an admin for an invite"
+                    .to_string()
+            }
+            e => format!("Failed to register member: {}", e),
+        })?;
+
+        let member = Self::link_identity(&mut tx, member_id, &identity).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit registration: {}", e))?;
+
+        WebhookService::try_enqueue(
+            pool,
+            WebhookEvent::MemberCreated,
+            serde_json::json!({ "memberId": member.member_id, "name": member.name }),
+        )
+        .await;
 
         Ok(member)
     }
@@ -1,4 +1,5 @@
 use crate::auth::AuthContext;
+use crate::models::auth::Permission;
 use async_graphql::{Context, Error, Guard, Result};
 
 pub struct AuthGuard;
@@ -52,3 +53,27 @@ impl Guard for AdminOrBotGuard {
         }
     }
 }
+
+/// Requires a permission, e.g. `PermissionGuard("attendance.correct")`. Roles scoped to a
+/// track or group pass, so resolvers acting on particular members check the scope with
+/// `AuthContext::has_permission_over`.
+pub struct PermissionGuard(pub &'static str);
+
+impl Guard for PermissionGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let auth = ctx.data::<AuthContext>().map_err(|_| {
+            Error::new("Authentication context not found. This is an internal server error.")
+        })?;
+
+        let permission: Permission = self.0.parse().map_err(Error::new)?;
+
+        if auth.has_permission_anywhere(permission) {
+            Ok(())
+        } else {
+            Err(Error::new(format!(
+                "The {} permission is required for this operation",
+                self.0
+            )))
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::auth::oauth::GitHubOAuthService;
+use crate::auth::oidc::{OidcConfig, OidcProvider};
+use async_trait::async_trait;
+use oauth2::CsrfToken;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// `/auth/email` serves email login, so no provider can use the name
+pub const EMAIL_LOGIN_PATH: &str = "email";
+
+/// An account at an identity provider, as returned after a successful login
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub provider: String,
+    /// The provider's stable ID for the account
+    pub subject: String,
+    pub username: Option<String>,
+    pub name: String,
+    pub email: Option<String>,
+    /// Addresses the provider has verified, used to link existing members
+    pub verified_emails: Vec<String>,
+}
+
+impl ExternalIdentity {
+    /// Verified addresses, starting with `email` if it is one of them
+    pub fn verified_emails_by_preference(&self) -> impl Iterator<Item = &String> {
+        let preferred = self.email.as_ref().and_then(|email| {
+            self.verified_emails
+                .iter()
+                .find(|v| v.eq_ignore_ascii_case(email))
+        });
+
+        preferred.into_iter().chain(
+            self.verified_emails
+                .iter()
+                .filter(move |v| Some(*v) != preferred),
+        )
+    }
+}
+
+/// A login method using the OAuth authorization code flow, served at `/auth/{name}`.
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Name used in the routes and stored with linked identities
+    fn name(&self) -> &str;
+
+    fn authorization_url(&self) -> (String, CsrfToken);
+
+    /// Exchange the code from the callback and fetch the account
+    async fn authenticate(&self, code: String) -> Result<ExternalIdentity, String>;
+
+    /// The verified address to register the account as a new member with, if it matches no
+    /// member and the provider lets it register itself. Without one an invite is needed.
+    fn registration_email(&self, _identity: &ExternalIdentity) -> Option<String> {
+        None
+    }
+}
+
+/// The identity providers members can log in with, keyed by name
+#[derive(Clone, Default)]
+pub struct IdentityProviders {
+    providers: HashMap<String, Arc<dyn IdentityProvider>>,
+}
+
+impl IdentityProviders {
+    /// GitHub, if `GITHUB_CLIENT_ID` and friends are set, and every provider listed in
+    /// `OIDC_PROVIDERS`. Providers that fail to configure are skipped.
+    pub async fn from_env() -> Self {
+        let mut providers = Self::default();
+
+        match GitHubOAuthService::new() {
+            Ok(github) => providers = providers.register(github),
+            Err(e) => warn!("GitHub login disabled: {}", e),
+        }
+
+        let names = std::env::var("OIDC_PROVIDERS").unwrap_or_default();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let name = name.to_lowercase();
+            if name == EMAIL_LOGIN_PATH {
+                warn!("Identity provider name {} is reserved, skipping", name);
+                continue;
+            }
+            if providers.get(&name).is_some() {
+                warn!("Identity provider {} is configured twice, skipping", name);
+                continue;
+            }
+
+            let provider = match OidcConfig::from_env(&name) {
+                Ok(config) => OidcProvider::new(config).await,
+                Err(e) => Err(e),
+            };
+            match provider {
+                Ok(provider) => providers = providers.register(provider),
+                Err(e) => warn!("Identity provider {} disabled: {}", name, e),
+            }
+        }
+
+        info!(
+            "Identity providers: {}",
+            providers
+                .providers
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        providers
+    }
+
+    pub fn register(mut self, provider: impl IdentityProvider + 'static) -> Self {
+        self.providers
+            .insert(provider.name().to_string(), Arc::new(provider));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn IdentityProvider>> {
+        self.providers.get(name).cloned()
+    }
+}
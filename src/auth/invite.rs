@@ -0,0 +1,50 @@
+use crate::auth::session::SessionService;
+use crate::models::auth::MemberInvite;
+use chrono::Duration;
+use sqlx::{PgPool, Postgres, Transaction};
+
+pub struct InviteService;
+
+impl InviteService {
+    /// Create a one-time invite code for `member_id`. Returns the invite and the plaintext
+    /// code, which isn't stored.
+    pub async fn create_invite(
+        pool: &PgPool,
+        member_id: i32,
+        created_by: i32,
+        expires_in_days: i64,
+    ) -> Result<(MemberInvite, String), String> {
+        let code = SessionService::generate_token();
+
+        let invite = sqlx::query_as::<_, MemberInvite>(
+            "INSERT INTO MemberInvite (member_id, code_hash, created_by, expires_at)
+            VALUES ($1, $2, $3, NOW() + $4)
+            RETURNING *",
+        )
+        .bind(member_id)
+        .bind(SessionService::hash_token(&code))
+        .bind(created_by)
+        .bind(Duration::days(expires_in_days))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to create invite: {}", e))?;
+
+        Ok((invite, code))
+    }
+
+    /// Mark an unused, unexpired invite as used. Returns the member it was created for.
+    pub async fn redeem(
+        tx: &mut Transaction<'_, Postgres>,
+        code: &str,
+    ) -> Result<Option<i32>, String> {
+        sqlx::query_scalar(
+            "UPDATE MemberInvite SET used_at = NOW()
+            WHERE code_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING member_id",
+        )
+        .bind(SessionService::hash_token(code))
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to redeem invite: {}", e))
+    }
+}
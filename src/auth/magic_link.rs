@@ -0,0 +1,166 @@
+use crate::auth::session::SessionService;
+use crate::mailer::{Email, Mailer};
+use crate::models::member::Member;
+use chrono::Duration;
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+const TOKEN_DURATION_MINUTES: i64 = 15;
+
+/// Links sent to a member in one token lifetime. Further requests are ignored.
+const MAX_PENDING_TOKENS: i64 = 3;
+
+/// Passwordless login. A member requests a link to their email, and the single-use token
+/// in it is exchanged for a session at `/auth/email/callback`.
+#[derive(Clone)]
+pub struct MagicLinkService {
+    mailer: Arc<dyn Mailer>,
+    /// `EMAIL_LOGIN_URL`, e.g. `http://localhost:5000/auth/email/callback`. Email login is
+    /// disabled without it.
+    link_url: Option<String>,
+}
+
+impl MagicLinkService {
+    pub fn from_env(mailer: Arc<dyn Mailer>) -> Self {
+        Self {
+            mailer,
+            link_url: env::var("EMAIL_LOGIN_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+        }
+    }
+
+    /// Email a login link to the member with this email or email alias. The member is looked
+    /// up and the link sent in the background, and nothing is sent if there is no such
+    /// member, so that neither the response, its timing nor errors tell whether the email
+    /// belongs to a member.
+    pub fn request_link(&self, pool: &PgPool, email: &str) -> Result<(), String> {
+        let link_url = self
+            .link_url
+            .clone()
+            .ok_or("Email login is not configured")?;
+
+        let service = self.clone();
+        let pool = pool.clone();
+        let email = email.trim().to_string();
+        tokio::spawn(async move {
+            if let Err(e) = service.send_link(&pool, &email, &link_url).await {
+                warn!("Failed to send login link: {}", e);
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn send_link(&self, pool: &PgPool, email: &str, link_url: &str) -> Result<(), String> {
+        let member = sqlx::query_as::<_, Member>(
+            "SELECT m.* FROM Member m
+            WHERE m.role <> 'Bot' AND m.membership_status <> 'Suspended'
+            AND (
+                LOWER(m.email) = LOWER($1)
+                OR m.member_id IN (
+                    SELECT member_id FROM MemberEmailAlias WHERE LOWER(email) = LOWER($1)
+                )
+            )
+            LIMIT 1",
+        )
+        .bind(email)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to query member: {}", e))?;
+
+        let Some(member) = member else {
+            info!("Login link requested for unknown email");
+            return Ok(());
+        };
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        // Concurrent requests for the same member wait here, so that they can't all count
+        // the pending tokens before any of them is inserted
+        sqlx::query("SELECT 1 FROM Member WHERE member_id = $1 FOR UPDATE")
+            .bind(member.member_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to lock member: {}", e))?;
+
+        let pending: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM LoginToken
+            WHERE member_id = $1 AND used_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(member.member_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to count login tokens: {}", e))?;
+
+        if pending >= MAX_PENDING_TOKENS {
+            info!(
+                "Too many pending login links for member {}, not sending another",
+                member.member_id
+            );
+            return Ok(());
+        }
+
+        let token = SessionService::generate_token();
+        sqlx::query(
+            "INSERT INTO LoginToken (member_id, token_hash, expires_at)
+            VALUES ($1, $2, NOW() + $3)",
+        )
+        .bind(member.member_id)
+        .bind(SessionService::hash_token(&token))
+        .bind(Duration::minutes(TOKEN_DURATION_MINUTES))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create login token: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit login token: {}", e))?;
+
+        // The link is sent to the address that was asked for, which may be an alias
+        self.mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Your Root login link".to_string(),
+                body: format!(
+                    "Hi {},\n\nUse this link to log in to Root. It expires in {} minutes and \
+                    can only be used once.\n\n{}?token={}\n\nIf you didn't ask for it, you can \
+                    ignore this email.",
+                    member.name, TOKEN_DURATION_MINUTES, link_url, token
+                ),
+            })
+            .await
+    }
+
+    /// Use up a login token. Returns the member it was sent to, if it was valid.
+    pub async fn redeem(pool: &PgPool, token: &str) -> Result<Option<Member>, String> {
+        sqlx::query_as::<_, Member>(
+            "WITH used AS (
+                UPDATE LoginToken SET used_at = NOW()
+                WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+                RETURNING member_id
+            )
+            SELECT m.* FROM Member m
+            JOIN used u ON u.member_id = m.member_id
+            WHERE m.membership_status <> 'Suspended'",
+        )
+        .bind(SessionService::hash_token(token))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to redeem login token: {}", e))
+    }
+
+    pub async fn cleanup_expired_tokens(pool: &PgPool) -> Result<u64, String> {
+        let result = sqlx::query("DELETE FROM LoginToken WHERE expires_at <= NOW()")
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to cleanup login tokens: {}", e))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,6 +1,11 @@
+use crate::audit::AuditService;
 use crate::auth::api_key::ApiKeyService;
+use crate::auth::permissions::PermissionService;
 use crate::auth::session::SessionService;
 use crate::auth::AuthContext;
+use crate::client_ip::ClientIp;
+use crate::models::audit::AuditEvent;
+use crate::models::member::Member;
 use axum::{
     extract::Request,
     http::{header::AUTHORIZATION, StatusCode},
@@ -23,22 +28,60 @@ pub async fn auth_middleware(
 
     let jar = CookieJar::from_headers(request.headers());
 
-    let member = if let Some(cookie) = jar.get("session_token") {
-        SessionService::validate_session(&pool, cookie.value())
-            .await
-            .ok()
-            .flatten()
+    let (member, impersonator) = if let Some(cookie) = jar.get("session_token") {
+        match SessionService::validate_session(&pool, cookie.value()).await {
+            Ok(Some((member, impersonator))) => (Some(member), impersonator),
+            _ => (None, None),
+        }
     } else if let Some(auth_value) = auth_header {
         let token = auth_value.strip_prefix("Bearer ").unwrap_or(auth_value);
-        ApiKeyService::validate_api_key(&pool, token)
+        let member = ApiKeyService::validate_api_key(&pool, token)
             .await
             .ok()
-            .flatten()
+            .flatten();
+        let client_ip = request.extensions().get::<ClientIp>().copied();
+        let route = format!("{} {}", request.method(), request.uri().path());
+        audit_api_key(&pool, client_ip, route, member.as_ref()).await;
+        (member, None)
     } else {
-        None
+        (None, None)
     };
+
+    // Bots have negative IDs and no access roles
+    let access_roles = match &member {
+        Some(member) if member.member_id > 0 => {
+            PermissionService::access_roles(&pool, member.member_id)
+                .await
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+
     // Inject auth context into request extensions
-    request.extensions_mut().insert(AuthContext::new(member));
+    request
+        .extensions_mut()
+        .insert(AuthContext::new(member, impersonator, access_roles));
 
     Ok(next.run(request).await)
 }
+
+async fn audit_api_key(
+    pool: &PgPool,
+    client_ip: Option<ClientIp>,
+    route: String,
+    bot: Option<&Member>,
+) {
+    let mut entry = AuditService::entry(None, client_ip.as_ref());
+    entry.operation = Some(route);
+
+    let event = match bot {
+        Some(bot) => {
+            entry.actor_id = Some(bot.member_id);
+            entry.actor_role = Some(bot.role);
+            AuditEvent::ApiKeyUsed
+        }
+        None => AuditEvent::ApiKeyRejected,
+    };
+
+    AuditService::try_record(pool, event, entry).await;
+}
@@ -1,27 +1,52 @@
 pub mod api_key;
 pub mod auth_service;
 pub mod guards;
+pub mod identity;
+pub mod invite;
+pub mod magic_link;
 pub mod middleware;
 pub mod oauth;
+pub mod oidc;
+pub mod permissions;
 pub mod session;
 
-use crate::models::auth::Role;
+use crate::models::auth::{MemberAccessRole, Permission, Role};
 use crate::models::member::Member;
 
 #[derive(Clone, Debug)]
 pub struct AuthContext {
+    /// The member requests are made as. During impersonation, the impersonated member.
     pub user: Option<Member>,
+    /// The admin impersonating `user`, if any
+    pub impersonator: Option<Member>,
+    pub access_roles: Vec<MemberAccessRole>,
 }
 
 impl AuthContext {
-    pub fn new(user: Option<Member>) -> Self {
-        Self { user }
+    pub fn new(
+        user: Option<Member>,
+        impersonator: Option<Member>,
+        access_roles: Vec<MemberAccessRole>,
+    ) -> Self {
+        Self {
+            user,
+            impersonator,
+            access_roles,
+        }
     }
 
     pub fn user(&self) -> Option<&Member> {
         self.user.as_ref()
     }
 
+    pub fn impersonator(&self) -> Option<&Member> {
+        self.impersonator.as_ref()
+    }
+
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonator.is_some()
+    }
+
     pub fn role(&self) -> Option<Role> {
         self.user.as_ref().map(|u| u.role)
     }
@@ -41,4 +66,46 @@ impl AuthContext {
     pub fn is_bot(&self) -> bool {
         self.has_role(Role::Bot)
     }
+
+    /// Admins have every permission. Bots can read private member details, e.g. to match
+    /// MAC addresses for attendance.
+    fn base_role_grants(&self, permission: Permission) -> bool {
+        match self.role() {
+            Some(Role::Admin) => true,
+            Some(Role::Bot) => permission == Permission::MembersReadPrivate,
+            _ => false,
+        }
+    }
+
+    fn roles_granting(&self, permission: Permission) -> impl Iterator<Item = &MemberAccessRole> {
+        self.access_roles
+            .iter()
+            .filter(move |a| a.role.permissions().contains(&permission))
+    }
+
+    /// Whether the user has `permission` over every member
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.base_role_grants(permission)
+            || self
+                .roles_granting(permission)
+                .any(|a| a.track_id.is_none() && a.group_id.is_none())
+    }
+
+    /// Whether the user has `permission` over at least some members
+    pub fn has_permission_anywhere(&self, permission: Permission) -> bool {
+        self.base_role_grants(permission) || self.roles_granting(permission).next().is_some()
+    }
+
+    /// Whether the user has `permission` over `member`, through an unscoped role or one
+    /// scoped to the member's track or group
+    pub fn has_permission_over(&self, permission: Permission, member: &Member) -> bool {
+        self.base_role_grants(permission)
+            || self
+                .roles_granting(permission)
+                .any(|a| match (a.track_id, a.group_id) {
+                    (None, None) => true,
+                    (Some(track_id), _) => member.track_id == Some(track_id),
+                    (_, Some(group_id)) => member.group_id == Some(group_id),
+                })
+    }
 }
@@ -1,4 +1,6 @@
+use crate::auth::identity::{ExternalIdentity, IdentityProvider};
 use crate::models::auth::GitHubUser;
+use async_trait::async_trait;
 use oauth2::{
     basic::BasicClient, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl,
     Scope, TokenResponse, TokenUrl,
@@ -7,12 +9,17 @@ use reqwest;
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Name of the GitHub identity provider. Its identities are mirrored to `Member.github_id`
+/// and `Member.github_user`.
+pub const GITHUB_PROVIDER: &str = "github";
+
 #[derive(Debug, Clone)]
 pub struct GitHubOAuthConfig {
     pub client_id: String,
     pub client_secret: String,
     pub redirect_url: String,
     pub org_name: String,
+    pub api_url: String,
 }
 
 impl GitHubOAuthConfig {
@@ -25,6 +32,7 @@ impl GitHubOAuthConfig {
             redirect_url: env::var("GITHUB_REDIRECT_URL")
                 .map_err(|_| "GITHUB_REDIRECT_URL not set".to_string())?,
             org_name: env::var("GITHUB_ORG_NAME").unwrap_or_else(|_| "amfoss".to_string()),
+            api_url: crate::github::api_url_from_env(),
         })
     }
 
@@ -103,7 +111,7 @@ impl GitHubOAuthService {
         let client = reqwest::Client::new();
 
         let user_response: GitHubUserResponse = client
-            .get("https://api.github.com/user")
+            .get(format!("{}/user", self.config.api_url))
             .header("Authorization", format!("Bearer {}", access_token))
             .header("User-Agent", "Root-Backend")
             .send()
@@ -113,33 +121,40 @@ impl GitHubOAuthService {
             .await
             .map_err(|e| format!("Failed to parse user info: {}", e))?;
 
-        // Fetch from /user/emails if not in profile
-        let email = if let Some(email) = user_response.email {
-            email
-        } else {
-            let emails: Vec<GitHubEmailResponse> = client
-                .get("https://api.github.com/user/emails")
-                .header("Authorization", format!("Bearer {}", access_token))
-                .header("User-Agent", "Root-Backend")
-                .send()
-                .await
-                .map_err(|e| format!("Failed to fetch user emails: {}", e))?
-                .json()
-                .await
-                .map_err(|e| format!("Failed to parse user emails: {}", e))?;
-
-            emails
-                .into_iter()
+        let emails: Vec<GitHubEmailResponse> = client
+            .get(format!("{}/user/emails", self.config.api_url))
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("User-Agent", "Root-Backend")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch user emails: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse user emails: {}", e))?;
+
+        // Use the profile email if there is one, otherwise the primary address
+        let email = match user_response.email {
+            Some(email) => email,
+            None => emails
+                .iter()
                 .find(|e| e.primary && e.verified)
                 .ok_or("No verified primary email found".to_string())?
                 .email
+                .clone(),
         };
 
+        let verified_emails = emails
+            .into_iter()
+            .filter(|e| e.verified)
+            .map(|e| e.email)
+            .collect();
+
         Ok(GitHubUser {
             github_id: user_response.id,
             github_username: user_response.login,
             name: user_response.name.unwrap_or_else(|| "Unknown".to_string()),
             email,
+            verified_emails,
         })
     }
 
@@ -151,8 +166,8 @@ impl GitHubOAuthService {
         let client = reqwest::Client::new();
 
         let url = format!(
-            "https://api.github.com/orgs/{}/members/{}",
-            self.config.org_name, username
+            "{}/orgs/{}/members/{}",
+            self.config.api_url, self.config.org_name, username
         );
 
         let response = client
@@ -186,3 +201,32 @@ impl GitHubOAuthService {
         Ok(user_info)
     }
 }
+
+#[async_trait]
+impl IdentityProvider for GitHubOAuthService {
+    fn name(&self) -> &str {
+        GITHUB_PROVIDER
+    }
+
+    fn authorization_url(&self) -> (String, CsrfToken) {
+        self.get_authorization_url()
+    }
+
+    async fn authenticate(&self, code: String) -> Result<ExternalIdentity, String> {
+        let github_user = self.complete_oauth_flow(code).await?;
+
+        Ok(ExternalIdentity {
+            provider: GITHUB_PROVIDER.to_string(),
+            subject: github_user.github_id.to_string(),
+            username: Some(github_user.github_username),
+            name: github_user.name,
+            email: Some(github_user.email),
+            verified_emails: github_user.verified_emails,
+        })
+    }
+
+    /// Only organization members get this far, so they can register
+    fn registration_email(&self, identity: &ExternalIdentity) -> Option<String> {
+        identity.verified_emails_by_preference().next().cloned()
+    }
+}
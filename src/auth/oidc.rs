@@ -0,0 +1,196 @@
+use crate::auth::identity::{ExternalIdentity, IdentityProvider};
+use async_trait::async_trait;
+use oauth2::{
+    basic::BasicClient, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl,
+    Scope, TokenResponse, TokenUrl,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::env;
+
+/// Settings of a generic OpenID Connect provider, read from `OIDC_<NAME>_*` variables.
+/// Endpoints that aren't set are discovered from the issuer.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    pub scopes: Vec<String>,
+    pub auth_url: Option<String>,
+    pub token_url: Option<String>,
+    pub userinfo_url: Option<String>,
+    /// Lowercase domains whose verified addresses may register as new members
+    pub allowed_domains: Vec<String>,
+}
+
+impl OidcConfig {
+    pub fn from_env(name: &str) -> Result<Self, String> {
+        let prefix = format!("OIDC_{}_", name.to_uppercase().replace('-', "_"));
+        let var = |key: &str| env::var(format!("{}{}", prefix, key)).ok();
+        let required = |key: &str| var(key).ok_or_else(|| format!("{}{} not set", prefix, key));
+
+        Ok(Self {
+            name: name.to_string(),
+            issuer: required("ISSUER")?.trim_end_matches('/').to_string(),
+            client_id: required("CLIENT_ID")?,
+            client_secret: required("CLIENT_SECRET")?,
+            redirect_url: required("REDIRECT_URL")?,
+            scopes: var("SCOPES")
+                .unwrap_or_else(|| "openid email profile".to_string())
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            auth_url: var("AUTH_URL"),
+            token_url: var("TOKEN_URL"),
+            userinfo_url: var("USERINFO_URL"),
+            allowed_domains: var("ALLOWED_DOMAINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+pub struct OidcProvider {
+    name: String,
+    client: BasicClient,
+    scopes: Vec<String>,
+    userinfo_url: String,
+    allowed_domains: Vec<String>,
+    http: reqwest::Client,
+}
+
+impl OidcProvider {
+    pub async fn new(config: OidcConfig) -> Result<Self, String> {
+        let http = reqwest::Client::new();
+
+        let (auth_url, token_url, userinfo_url) =
+            match (config.auth_url, config.token_url, config.userinfo_url) {
+                (Some(auth), Some(token), Some(userinfo)) => (auth, token, userinfo),
+                (auth, token, userinfo) => {
+                    let discovery: Discovery = http
+                        .get(format!(
+                            "{}/.well-known/openid-configuration",
+                            config.issuer
+                        ))
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status())
+                        .map_err(|e| format!("Failed to fetch OIDC discovery document: {}", e))?
+                        .json()
+                        .await
+                        .map_err(|e| format!("Failed to parse OIDC discovery document: {}", e))?;
+                    (
+                        auth.unwrap_or(discovery.authorization_endpoint),
+                        token.unwrap_or(discovery.token_endpoint),
+                        userinfo.unwrap_or(discovery.userinfo_endpoint),
+                    )
+                }
+            };
+
+        let client = BasicClient::new(
+            ClientId::new(config.client_id),
+            Some(ClientSecret::new(config.client_secret)),
+            AuthUrl::new(auth_url).map_err(|e| format!("Invalid auth URL: {}", e))?,
+            Some(TokenUrl::new(token_url).map_err(|e| format!("Invalid token URL: {}", e))?),
+        )
+        .set_redirect_uri(
+            RedirectUrl::new(config.redirect_url)
+                .map_err(|e| format!("Invalid redirect URL: {}", e))?,
+        );
+
+        Ok(Self {
+            name: config.name,
+            client,
+            scopes: config.scopes,
+            userinfo_url,
+            allowed_domains: config.allowed_domains,
+            http,
+        })
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn authorization_url(&self) -> (String, CsrfToken) {
+        let (auth_url, csrf_token) = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(self.scopes.iter().cloned().map(Scope::new))
+            .url();
+
+        (auth_url.to_string(), csrf_token)
+    }
+
+    async fn authenticate(&self, code: String) -> Result<ExternalIdentity, String> {
+        let token = self
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .request_async(oauth2::reqwest::async_http_client)
+            .await
+            .map_err(|e| format!("Failed to exchange code: {}", e))?;
+
+        // The userinfo endpoint is queried with the access token rather than validating
+        // the ID token, so no signing keys are needed.
+        let claims: Value = self
+            .http
+            .get(&self.userinfo_url)
+            .bearer_auth(token.access_token().secret())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch user info: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse user info: {}", e))?;
+
+        let subject = claims["sub"]
+            .as_str()
+            .ok_or("User info has no subject")?
+            .to_string();
+        let email = claims["email"].as_str().map(str::to_string);
+        // Some providers send the flag as a string
+        let email_verified = matches!(&claims["email_verified"], Value::Bool(true))
+            || claims["email_verified"] == "true";
+        let username = claims["preferred_username"].as_str().map(str::to_string);
+
+        Ok(ExternalIdentity {
+            provider: self.name.clone(),
+            subject,
+            name: claims["name"]
+                .as_str()
+                .or(username.as_deref())
+                .unwrap_or("Unknown")
+                .to_string(),
+            username,
+            verified_emails: email.iter().filter(|_| email_verified).cloned().collect(),
+            email,
+        })
+    }
+
+    fn registration_email(&self, identity: &ExternalIdentity) -> Option<String> {
+        identity
+            .verified_emails_by_preference()
+            .find(|email| {
+                email.rsplit_once('@').is_some_and(|(_, domain)| {
+                    self.allowed_domains.contains(&domain.to_lowercase())
+                })
+            })
+            .cloned()
+    }
+}
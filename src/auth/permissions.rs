@@ -0,0 +1,66 @@
+use crate::models::auth::{AccessRole, MemberAccessRole};
+use sqlx::PgPool;
+
+pub struct PermissionService;
+
+impl PermissionService {
+    pub async fn access_roles(
+        pool: &PgPool,
+        member_id: i32,
+    ) -> Result<Vec<MemberAccessRole>, String> {
+        sqlx::query_as::<_, MemberAccessRole>(
+            "SELECT * FROM MemberAccessRole WHERE member_id = $1 ORDER BY assignment_id",
+        )
+        .bind(member_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch access roles: {}", e))
+    }
+
+    /// Assign `role` to a member, optionally scoped to a track or a group but not both.
+    pub async fn assign_role(
+        pool: &PgPool,
+        member_id: i32,
+        role: AccessRole,
+        track_id: Option<i32>,
+        group_id: Option<i32>,
+        assigned_by: i32,
+    ) -> Result<MemberAccessRole, String> {
+        if track_id.is_some() && group_id.is_some() {
+            return Err("A role can be scoped to a track or a group, not both".to_string());
+        }
+
+        sqlx::query_as::<_, MemberAccessRole>(
+            "INSERT INTO MemberAccessRole (member_id, role, track_id, group_id, assigned_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *",
+        )
+        .bind(member_id)
+        .bind(role)
+        .bind(track_id)
+        .bind(group_id)
+        .bind(assigned_by)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                "The member already has this role".to_string()
+            }
+            sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+                "Member, track or group not found".to_string()
+            }
+            e => format!("Failed to assign role: {}", e),
+        })
+    }
+
+    /// Returns whether the assignment existed.
+    pub async fn revoke_role(pool: &PgPool, assignment_id: i32) -> Result<bool, String> {
+        let result = sqlx::query("DELETE FROM MemberAccessRole WHERE assignment_id = $1")
+            .bind(assignment_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to revoke role: {}", e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
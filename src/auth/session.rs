@@ -1,17 +1,39 @@
 use crate::models::member::Member;
+use crate::Config;
+use axum_extra::extract::cookie::{Cookie, SameSite};
 use chrono::{Duration, Utc};
 use chrono_tz::Asia::Kolkata;
 use rand::Rng;
 use sha2::{Digest, Sha256};
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool};
 
-const SESSION_DURATION_DAYS: i64 = 30;
+pub const SESSION_DURATION_DAYS: i64 = 30;
+pub const IMPERSONATION_DURATION_HOURS: i64 = 1;
 const TOKEN_LENGTH: usize = 64;
 
+#[derive(FromRow)]
+struct SessionMember {
+    #[sqlx(flatten)]
+    member: Member,
+    impersonator_id: Option<i32>,
+}
+
+/// The cookie that holds a session token
+pub fn session_cookie(config: &Config, token: String, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build(("session_token", token))
+        .path("/")
+        .http_only(true)
+        .secure(config.env != "development")
+        .domain(config.hostname.clone())
+        .same_site(SameSite::Lax)
+        .max_age(max_age)
+        .build()
+}
+
 pub struct SessionService;
 
 impl SessionService {
-    fn generate_token() -> String {
+    pub(crate) fn generate_token() -> String {
         let mut rng = rand::thread_rng();
         let token: String = (0..TOKEN_LENGTH)
             .map(|_| {
@@ -26,26 +48,51 @@ impl SessionService {
         token
     }
 
-    fn hash_token(token: &str) -> String {
+    pub(crate) fn hash_token(token: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(token.as_bytes());
         format!("{:x}", hasher.finalize())
     }
 
     pub async fn create_session(pool: &PgPool, member_id: i32) -> Result<String, String> {
+        Self::insert_session(pool, member_id, None, Duration::days(SESSION_DURATION_DAYS)).await
+    }
+
+    /// Start a short session in which `impersonator_id` acts as `member_id`.
+    pub async fn create_impersonation_session(
+        pool: &PgPool,
+        member_id: i32,
+        impersonator_id: i32,
+    ) -> Result<String, String> {
+        Self::insert_session(
+            pool,
+            member_id,
+            Some(impersonator_id),
+            Duration::hours(IMPERSONATION_DURATION_HOURS),
+        )
+        .await
+    }
+
+    async fn insert_session(
+        pool: &PgPool,
+        member_id: i32,
+        impersonator_id: Option<i32>,
+        duration: Duration,
+    ) -> Result<String, String> {
         let token = Self::generate_token();
         let token_hash = Self::hash_token(&token);
-        let expires_at = Utc::now().with_timezone(&Kolkata) + Duration::days(SESSION_DURATION_DAYS);
+        let expires_at = Utc::now().with_timezone(&Kolkata) + duration;
 
         sqlx::query(
             r#"
-            INSERT INTO Sessions (member_id, token_hash, expires_at)
-            VALUES ($1, $2, $3)
+            INSERT INTO Sessions (member_id, token_hash, expires_at, impersonator_id)
+            VALUES ($1, $2, $3, $4)
             "#,
         )
         .bind(member_id)
         .bind(token_hash)
         .bind(expires_at)
+        .bind(impersonator_id)
         .execute(pool)
         .await
         .map_err(|e| format!("Failed to create session: {}", e))?;
@@ -53,15 +100,22 @@ impl SessionService {
         Ok(token)
     }
 
-    pub async fn validate_session(pool: &PgPool, token: &str) -> Result<Option<Member>, String> {
+    /// The member a session belongs to, and the admin impersonating them if it is an
+    /// impersonation session. Impersonation sessions end once the impersonator is no
+    /// longer an admin.
+    pub async fn validate_session(
+        pool: &PgPool,
+        token: &str,
+    ) -> Result<Option<(Member, Option<Member>)>, String> {
         let token_hash = Self::hash_token(token);
         let now = chrono::Utc::now().with_timezone(&Kolkata);
 
-        let result = sqlx::query_as::<_, Member>(
+        let result = sqlx::query_as::<_, SessionMember>(
             r#"
-            SELECT m.* FROM Member m
+            SELECT m.*, s.impersonator_id FROM Member m
             INNER JOIN Sessions s ON m.member_id = s.member_id
             WHERE s.token_hash = $1 AND s.expires_at > $2
+            AND m.membership_status <> 'Suspended'
             "#,
         )
         .bind(token_hash)
@@ -70,7 +124,58 @@ impl SessionService {
         .await
         .map_err(|e| format!("Failed to validate session: {}", e))?;
 
-        Ok(result)
+        let Some(SessionMember {
+            member,
+            impersonator_id,
+        }) = result
+        else {
+            return Ok(None);
+        };
+
+        let Some(impersonator_id) = impersonator_id else {
+            return Ok(Some((member, None)));
+        };
+
+        let impersonator = sqlx::query_as::<_, Member>(
+            "SELECT * FROM Member
+            WHERE member_id = $1 AND role = 'Admin' AND membership_status <> 'Suspended'",
+        )
+        .bind(impersonator_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to validate session: {}", e))?;
+
+        Ok(impersonator.map(|impersonator| (member, Some(impersonator))))
+    }
+
+    /// End `impersonator_id`'s impersonation sessions as `member_id`.
+    pub async fn end_impersonation(
+        pool: &PgPool,
+        member_id: i32,
+        impersonator_id: i32,
+    ) -> Result<u64, String> {
+        let result =
+            sqlx::query("DELETE FROM Sessions WHERE member_id = $1 AND impersonator_id = $2")
+                .bind(member_id)
+                .bind(impersonator_id)
+                .execute(pool)
+                .await
+                .map_err(|e| format!("Failed to end impersonation: {}", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Log a member out everywhere, including sessions in which they impersonate others.
+    /// Returns the number of sessions deleted.
+    pub async fn revoke_member_sessions(pool: &PgPool, member_id: i32) -> Result<u64, String> {
+        let result =
+            sqlx::query("DELETE FROM Sessions WHERE member_id = $1 OR impersonator_id = $1")
+                .bind(member_id)
+                .execute(pool)
+                .await
+                .map_err(|e| format!("Failed to revoke sessions: {}", e))?;
+
+        Ok(result.rows_affected())
     }
 
     pub async fn cleanup_expired_sessions(pool: &PgPool) -> Result<u64, String> {
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{ConnectInfo, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::net::{IpAddr, SocketAddr};
+
+/// Address of the client a request came from, available as a request extension and in
+/// GraphQL context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    pub fn to_string_opt(self) -> Option<String> {
+        self.0.map(|ip| ip.to_string())
+    }
+}
+
+/// Record the client's address. Behind a reverse proxy, set `trust_forwarded_for` to use
+/// the first address of `X-Forwarded-For` instead of the proxy's. Clients can set the
+/// header themselves, so it must only be trusted when the proxy overwrites it.
+pub async fn client_ip_middleware(
+    trust_forwarded_for: bool,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let forwarded = trust_forwarded_for
+        .then(|| request.headers().get("x-forwarded-for"))
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+
+    let ip = forwarded.or_else(|| {
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip())
+    });
+
+    request.extensions_mut().insert(ClientIp(ip));
+    next.run(request).await
+}
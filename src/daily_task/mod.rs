@@ -1,138 +1,362 @@
+use crate::auth::magic_link::MagicLinkService;
 use crate::auth::session::SessionService;
-use chrono::NaiveTime;
+use async_trait::async_trait;
+use chrono::NaiveDate;
 use chrono_tz::Asia::Kolkata;
 use sqlx::PgPool;
-use std::sync::Arc;
-use tokio::time::sleep_until;
 use tracing::{debug, error, info};
 
-use crate::models::member::Member;
-
-pub async fn run_daily_task_at_midnight(pool: Arc<PgPool>) {
-    loop {
-        let now = chrono::Utc::now().with_timezone(&Kolkata);
-        let naive_midnight =
-            NaiveTime::from_hms_opt(00, 30, 00).expect("Hardcoded time must be valid");
-        let today_midnight = now
-            .with_time(naive_midnight)
-            .single()
-            .expect("Hardcoded time must be valid");
-
-        let next_midnight = if now >= today_midnight {
-            today_midnight + chrono::Duration::days(1)
-        } else {
-            today_midnight
-        };
-        debug!("next_midnight: {}", next_midnight);
+use crate::member_lifecycle::{AcademicCalendar, MemberLifecycleService};
+use crate::models::daily_task::DailyTaskRun;
+use crate::models::member::ACTIVE_MEMBER_FILTER;
+use crate::rate_limit::PostgresStore;
+use crate::scheduler::Job;
+
+/// Upper bound on how many past days a daily job will backfill on its own.
+/// Anything older has to be backfilled manually by an admin.
+const MAX_AUTOMATIC_BACKFILL_DAYS: i64 = 31;
+
+/// Upper bound on the size of a single manual backfill.
+pub const MAX_MANUAL_BACKFILL_DAYS: i64 = 366;
 
-        let duration_until_midnight = next_midnight.signed_duration_since(now);
-        info!("Sleeping for {}", duration_until_midnight.num_seconds());
-        let sleep_duration =
-            tokio::time::Duration::from_secs(duration_until_midnight.num_seconds() as u64);
+/// Deletes expired user sessions, email login tokens and rate limit counters.
+pub struct SessionCleanupJob;
 
-        sleep_until(tokio::time::Instant::now() + sleep_duration).await;
-        execute_daily_task(pool.clone()).await;
+#[async_trait]
+impl Job for SessionCleanupJob {
+    fn name(&self) -> &'static str {
+        "session_cleanup"
+    }
+
+    fn schedule(&self) -> &'static str {
+        "0 * * * *"
     }
-}
 
-/// This function does a number of things, including:
-/// * Insert new attendance records everyday for [`presense`](https://www.github.com/amfoss/presense) to update them later in the day.
-/// * Delete expired user sessions.
-async fn execute_daily_task(pool: Arc<PgPool>) {
-    if let Ok(rows_deleted) = SessionService::cleanup_expired_sessions(&pool).await {
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        let rows_deleted = SessionService::cleanup_expired_sessions(pool).await?;
         if rows_deleted > 0 {
-            tracing::info!("Cleaned up {:?} expired sessions", rows_deleted);
+            info!("Cleaned up {:?} expired sessions", rows_deleted);
         }
+
+        let tokens_deleted = MagicLinkService::cleanup_expired_tokens(pool).await?;
+        if tokens_deleted > 0 {
+            info!("Cleaned up {:?} expired login tokens", tokens_deleted);
+        }
+
+        let counters_deleted = PostgresStore::cleanup_expired(pool).await?;
+        if counters_deleted > 0 {
+            info!(
+                "Cleaned up {:?} expired rate limit counters",
+                counters_deleted
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Applies scheduled membership status changes and graduates final-year members at the start
+/// of each academic year. Runs before the daily row jobs, both on schedule and on startup,
+/// where it is registered first, so that their rows reflect the new statuses.
+pub struct MemberLifecycleJob {
+    pub calendar: AcademicCalendar,
+}
+
+#[async_trait]
+impl Job for MemberLifecycleJob {
+    fn name(&self) -> &'static str {
+        "member_lifecycle"
     }
 
-    // Members is queried outside of each function to avoid repetition
-    let members = sqlx::query_as::<_, Member>("SELECT * FROM Member")
-        .fetch_all(&*pool)
-        .await;
+    fn schedule(&self) -> &'static str {
+        "15 0 * * *"
+    }
 
-    match members {
-        Ok(members) => {
-            update_attendance(&members, &pool).await;
-            update_status_history(&members, &pool).await;
+    fn run_on_startup(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        let today = today();
+
+        let applied = MemberLifecycleService::apply_pending_changes(pool, today).await?;
+        if applied > 0 {
+            info!("Applied {} membership status changes", applied);
         }
-        // TODO: Handle this
-        Err(e) => error!("Failed to fetch members: {:?}", e),
-    };
+
+        MemberLifecycleService::promote_academic_year(pool, &self.calendar, today).await
+    }
 }
 
-async fn update_attendance(members: &Vec<Member>, pool: &PgPool) {
-    #[allow(deprecated)]
-    let today = chrono::Utc::now()
-        .with_timezone(&Kolkata)
-        .date()
-        .naive_local();
-    debug!("Updating attendance on {}", today);
-
-    for member in members {
-        let attendance = sqlx::query(
-            "INSERT INTO Attendance (member_id, date, is_present, time_in, time_out)
-                     VALUES ($1, $2, $3, $4, $5)
-                     ON CONFLICT (member_id, date) DO NOTHING",
+/// Inserts new attendance records everyday for [`presense`](https://www.github.com/amfoss/presense) to update them later in the day.
+pub struct AttendanceJob;
+
+#[async_trait]
+impl Job for AttendanceJob {
+    fn name(&self) -> &'static str {
+        DailyRecordKind::Attendance.job_name()
+    }
+
+    fn schedule(&self) -> &'static str {
+        "30 0 * * *"
+    }
+
+    fn run_on_startup(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        catch_up(pool, DailyRecordKind::Attendance).await
+    }
+}
+
+/// Inserts new status update history records everyday for the status update bot to mark later.
+pub struct StatusHistoryJob;
+
+#[async_trait]
+impl Job for StatusHistoryJob {
+    fn name(&self) -> &'static str {
+        DailyRecordKind::StatusHistory.job_name()
+    }
+
+    fn schedule(&self) -> &'static str {
+        "30 0 * * *"
+    }
+
+    fn run_on_startup(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        catch_up(pool, DailyRecordKind::StatusHistory).await
+    }
+}
+
+/// The kinds of rows generated for every member each day. Each kind is generated
+/// by its own job and tracked separately in `DailyTaskRun`.
+#[derive(Copy, Clone, Debug)]
+pub enum DailyRecordKind {
+    Attendance,
+    StatusHistory,
+}
+
+impl DailyRecordKind {
+    pub const ALL: [DailyRecordKind; 2] = [Self::Attendance, Self::StatusHistory];
+
+    pub fn job_name(self) -> &'static str {
+        match self {
+            Self::Attendance => "attendance",
+            Self::StatusHistory => "status_history",
+        }
+    }
+
+    /// Set-based insert of a row for every active member on `date`, as a single statement
+    /// so that either all members get a row or none do.
+    fn insert_query(self) -> String {
+        let (table, columns, values) = match self {
+            Self::Attendance => ("Attendance", "member_id, date, is_present", "FALSE"),
+            Self::StatusHistory => ("StatusUpdateHistory", "member_id, date, is_sent", "FALSE"),
+        };
+
+        format!(
+            "INSERT INTO {table} ({columns})
+            SELECT m.member_id, $1, {values}
+            FROM Member m
+            WHERE {ACTIVE_MEMBER_FILTER}
+            ON CONFLICT (member_id, date) DO NOTHING"
         )
-        .bind(member.member_id)
-        .bind(today)
-        .bind(false)
-        .bind(None::<NaiveTime>)
-        .bind(None::<NaiveTime>)
-        .execute(pool)
-        .await;
-
-        match attendance {
-            Ok(_) => {
-                debug!(
-                    "Attendance record added for member ID: {}",
-                    member.member_id
-                );
-            }
-            Err(e) => {
-                error!(
-                    "Failed to insert attendance for member ID: {}: {:?}",
-                    member.member_id, e
-                );
-            }
+    }
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Utc::now().with_timezone(&Kolkata).date_naive()
+}
+
+/// Generate rows for today and for every date missed since the last successful run, e.g. due to downtime.
+async fn catch_up(pool: &PgPool, kind: DailyRecordKind) -> Result<(), String> {
+    let dates = pending_dates(pool, kind, today()).await?;
+
+    if dates.len() > 1 {
+        info!(
+            "Backfilling {} records for {} days",
+            kind.job_name(),
+            dates.len() - 1
+        );
+    }
+
+    let mut failed = Vec::new();
+    for date in dates {
+        let run = run_for_date(pool, kind, date, None).await?;
+        if let Some(e) = run.error {
+            error!("Failed to generate {} for {}: {}", kind.job_name(), date, e);
+            failed.push(date.to_string());
         }
-        // This could have been called in `execute_daily_task()` but that would require us to loop through members twice.
     }
+
+    if !failed.is_empty() {
+        return Err(format!("Failed on {}", failed.join(", ")));
+    }
+    Ok(())
 }
 
-async fn update_status_history(members: &Vec<Member>, pool: &PgPool) {
-    #[allow(deprecated)]
-    let today = chrono::Utc::now()
-        .with_timezone(&Kolkata)
-        .date()
-        .naive_local();
-    debug!("Updating Status Update History on {}", today);
-
-    for member in members {
-        let status_update = sqlx::query(
-            "INSERT INTO StatusUpdateHistory (member_id, date, is_sent)
-                     VALUES ($1, $2, $3)
-                     ON CONFLICT (member_id, date) DO NOTHING",
+/// Every date from the day after the last successful run up to `today`, skipping dates
+/// that already succeeded (e.g. through a manual backfill).
+async fn pending_dates(
+    pool: &PgPool,
+    kind: DailyRecordKind,
+    today: NaiveDate,
+) -> Result<Vec<NaiveDate>, String> {
+    let last_success: Option<NaiveDate> = sqlx::query_scalar(
+        "SELECT MAX(run_date) FROM DailyTaskRun
+        WHERE job_name = $1 AND succeeded = TRUE AND run_date <= $2",
+    )
+    .bind(kind.job_name())
+    .bind(today)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch last successful run: {}", e))?;
+
+    let earliest = today - chrono::Duration::days(MAX_AUTOMATIC_BACKFILL_DAYS);
+    let start = match last_success {
+        Some(date) => (date + chrono::Duration::days(1)).max(earliest),
+        // First run ever, there is nothing to catch up on.
+        None => today,
+    };
+
+    let dates = sqlx::query_scalar(
+        "SELECT d::DATE FROM generate_series($2::DATE, $3::DATE, INTERVAL '1 day') AS d
+        WHERE NOT EXISTS (
+            SELECT 1 FROM DailyTaskRun
+            WHERE job_name = $1 AND run_date = d::DATE AND succeeded = TRUE
         )
-        .bind(member.member_id)
-        .bind(today)
-        .bind(false)
-        .execute(pool)
-        .await;
-
-        match status_update {
-            Ok(_) => {
-                debug!(
-                    "Status update record added for member ID: {}",
-                    member.member_id
-                );
-            }
-            Err(e) => {
-                error!(
-                    "Failed to insert status update history for member ID: {}: {:?}",
-                    member.member_id, e
-                );
-            }
+        ORDER BY d",
+    )
+    .bind(kind.job_name())
+    .bind(start)
+    .bind(today)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch pending dates: {}", e))?;
+
+    Ok(dates)
+}
+
+/// Generate every kind of daily row for each date in `start..=end`, regardless of earlier runs.
+/// Rows that already exist are left untouched.
+pub async fn backfill(
+    pool: &PgPool,
+    start: NaiveDate,
+    end: NaiveDate,
+    triggered_by: Option<i32>,
+) -> Result<Vec<DailyTaskRun>, String> {
+    if end < start {
+        return Err("end_date must be >= start_date".to_string());
+    }
+    if (end - start).num_days() >= MAX_MANUAL_BACKFILL_DAYS {
+        return Err(format!(
+            "Cannot backfill more than {} days at once",
+            MAX_MANUAL_BACKFILL_DAYS
+        ));
+    }
+
+    let mut runs = Vec::new();
+    let mut date = start;
+    while date <= end {
+        for kind in DailyRecordKind::ALL {
+            runs.push(run_for_date(pool, kind, date, triggered_by).await?);
+        }
+        date += chrono::Duration::days(1);
+    }
+
+    Ok(runs)
+}
+
+/// Generate one kind of row for a single date and record the outcome in `DailyTaskRun`.
+/// The rows and the successful run are committed together, so a run is never marked as
+/// succeeded without its rows.
+async fn run_for_date(
+    pool: &PgPool,
+    kind: DailyRecordKind,
+    date: NaiveDate,
+    triggered_by: Option<i32>,
+) -> Result<DailyTaskRun, String> {
+    debug!("Generating {} records for {}", kind.job_name(), date);
+
+    match generate(pool, kind, date, triggered_by).await {
+        Ok(run) => {
+            debug!(
+                "Created {} {} records for {}",
+                run.rows_created,
+                kind.job_name(),
+                date
+            );
+            Ok(run)
         }
+        Err(e) => record_run(pool, kind, date, triggered_by, Err(e)).await,
     }
 }
+
+async fn generate(
+    pool: &PgPool,
+    kind: DailyRecordKind,
+    date: NaiveDate,
+    triggered_by: Option<i32>,
+) -> Result<DailyTaskRun, String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let rows_created = sqlx::query(&kind.insert_query())
+        .bind(date)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to insert {} records: {}", kind.job_name(), e))?
+        .rows_affected();
+
+    let run = record_run(&mut *tx, kind, date, triggered_by, Ok(rows_created)).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit {} records: {}", kind.job_name(), e))?;
+
+    Ok(run)
+}
+
+async fn record_run<'e, E>(
+    executor: E,
+    kind: DailyRecordKind,
+    date: NaiveDate,
+    triggered_by: Option<i32>,
+    result: Result<u64, String>,
+) -> Result<DailyTaskRun, String>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let (rows_created, error) = match result {
+        Ok(rows) => (rows as i32, None),
+        Err(e) => (0, Some(e)),
+    };
+
+    sqlx::query_as::<_, DailyTaskRun>(
+        "INSERT INTO DailyTaskRun
+            (job_name, run_date, succeeded, error, rows_created, triggered_by, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, CLOCK_TIMESTAMP())
+        ON CONFLICT (job_name, run_date) DO UPDATE SET
+            succeeded = EXCLUDED.succeeded,
+            error = EXCLUDED.error,
+            rows_created = EXCLUDED.rows_created,
+            triggered_by = EXCLUDED.triggered_by,
+            started_at = EXCLUDED.started_at,
+            completed_at = EXCLUDED.completed_at
+        RETURNING *",
+    )
+    .bind(kind.job_name())
+    .bind(date)
+    .bind(error.is_none())
+    .bind(error)
+    .bind(rows_created)
+    .bind(triggered_by)
+    .fetch_one(executor)
+    .await
+    .map_err(|e| format!("Failed to record {} run: {}", kind.job_name(), e))
+}
@@ -1,6 +1,17 @@
+-- Groups and Tracks
+INSERT INTO MemberGroup (name)
+SELECT 'Group ' || i
+FROM generate_series(1, 8) AS i
+ON CONFLICT DO NOTHING;
+
+INSERT INTO Track (name)
+VALUES ('Web'), ('Systems'), ('AI'), ('Mobile')
+ON CONFLICT DO NOTHING;
+
+
 -- Member
 INSERT INTO member (
-    roll_no, name, email, sex, year, hostel, mac_address, discord_id, group_id, track, github_user
+    roll_no, name, email, sex, batch, hostel, mac_address, discord_id, group_id, track_id, github_user
 )
 SELECT 
     'R' || LPAD(i::TEXT, 4, '0'),
@@ -22,12 +33,12 @@ SELECT
         WHEN i % 2 = 0 THEN 'M'::sex_type 
         ELSE 'F'::sex_type 
     END,
-    (i % 4) + 1,
+    EXTRACT(YEAR FROM CURRENT_DATE - INTERVAL '6 months')::INT - (i % 4),
     'Hostel ' || ((i % 5) + 1),
     '00:14:22:01:' || LPAD(TO_HEX(i), 2, '0') || ':' || LPAD(TO_HEX(i + 60), 2, '0'),
     'discord_user_' || i,
-    (i % 8) + 1,
-    'track ' || ((i%4)+1),
+    (SELECT group_id FROM MemberGroup WHERE name = 'Group ' || ((i % 8) + 1)),
+    (SELECT track_id FROM Track ORDER BY track_id OFFSET (i % 4) LIMIT 1),
     'github_user_' || i
 FROM generate_series(1, 60) AS i
 ON CONFLICT (roll_no) DO NOTHING;
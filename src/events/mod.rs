@@ -0,0 +1,106 @@
+use chrono::NaiveDate;
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+pub mod postgres;
+
+/// Events buffered per subscriber before the slowest ones start missing events
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Something that changed, published after the change is committed. Events only carry ids,
+/// so subscribers load the current row themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    AttendanceMarked {
+        attendance_id: i32,
+        member_id: i32,
+        date: NaiveDate,
+    },
+    StatusUpdateReceived {
+        update_id: i32,
+        member_id: i32,
+        date: NaiveDate,
+    },
+    MemberUpdated {
+        member_id: i32,
+    },
+}
+
+/// Fans events out to GraphQL subscriptions. Events stay in this process by default; with
+/// the Postgres backend they're sent through `NOTIFY`, so subscribers connected to any
+/// instance receive them.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    pool: Option<Arc<PgPool>>,
+}
+
+impl EventBus {
+    /// Only delivers events published in this process
+    pub fn in_process() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender, pool: None }
+    }
+
+    /// Publishes through Postgres and relays notifications from every instance to local
+    /// subscribers. The listener connects to `database_url` itself rather than holding one
+    /// of the pool's connections for good.
+    pub fn postgres(pool: Arc<PgPool>, database_url: &str) -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        tokio::spawn(postgres::listen(database_url.to_string(), sender.clone()));
+        Self {
+            sender,
+            pool: Some(pool),
+        }
+    }
+
+    /// Configured by `EVENT_BUS`, either `memory` or `postgres`
+    pub fn from_env(pool: Arc<PgPool>, database_url: &str) -> Result<Self, String> {
+        let backend = std::env::var("EVENT_BUS").unwrap_or_else(|_| "memory".to_string());
+        let bus = match backend.as_str() {
+            "memory" => Self::in_process(),
+            "postgres" => Self::postgres(pool, database_url),
+            other => return Err(format!("Unknown EVENT_BUS {:?}", other)),
+        };
+
+        info!("Publishing events through {}", backend);
+        Ok(bus)
+    }
+
+    /// Send `event` to subscribers. Failures are logged, since the change that caused the
+    /// event has already been made.
+    pub async fn publish(&self, event: Event) {
+        match &self.pool {
+            Some(pool) => {
+                if let Err(e) = postgres::notify(pool.as_ref(), &event).await {
+                    warn!("Failed to publish {:?}: {}", event, e);
+                }
+            }
+            // Sending only fails when nobody is subscribed
+            None => {
+                let _ = self.sender.send(event);
+            }
+        }
+    }
+
+    /// Events published from now on. Subscribers that fall too far behind skip the events
+    /// they missed.
+    pub fn subscribe(&self) -> impl Stream<Item = Event> + Send + 'static {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Subscriber fell behind, skipped {} events", skipped)
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+use super::Event;
+
+/// `NOTIFY` channel shared by every instance
+const CHANNEL: &str = "root_events";
+
+pub async fn notify(pool: &PgPool, event: &Event) -> Result<(), String> {
+    let payload = serde_json::to_string(event).map_err(|e| e.to_string())?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(CHANNEL)
+        .bind(payload)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Relay notifications to local subscribers. Notifications sent while the connection is
+/// being re-established are lost.
+pub async fn listen(database_url: String, sender: broadcast::Sender<Event>) {
+    loop {
+        let mut listener = match PgListener::connect(&database_url).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to connect event listener: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        if let Err(e) = listener.listen(CHANNEL).await {
+            error!("Failed to listen for events: {}", e);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        loop {
+            match listener.recv().await {
+                Ok(notification) => match serde_json::from_str(notification.payload()) {
+                    Ok(event) => {
+                        let _ = sender.send(event);
+                    }
+                    Err(e) => warn!("Ignoring malformed event {:?}: {}", notification, e),
+                },
+                Err(e) => {
+                    error!("Event listener failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
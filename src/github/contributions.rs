@@ -0,0 +1,354 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Asia::Kolkata;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use tracing::{debug, info, warn};
+
+use crate::daily_task::today;
+use crate::github::{GitHubClient, GitHubEvent};
+use crate::scheduler::Job;
+
+/// GitHub only serves public events from the last 90 days.
+const EVENTS_WINDOW_DAYS: i64 = 90;
+
+/// GitHub serves at most 300 events per user, in pages of up to 100.
+const EVENTS_PER_PAGE: u32 = 100;
+const MAX_EVENT_PAGES: u32 = 3;
+
+/// Stores daily counts of each member's public commits, pull requests and reviews
+/// on the organization's repositories.
+pub struct GitHubContributionJob {
+    pub client: GitHubClient,
+}
+
+#[async_trait]
+impl Job for GitHubContributionJob {
+    fn name(&self) -> &'static str {
+        "github_contributions"
+    }
+
+    fn schedule(&self) -> &'static str {
+        "0 3 * * *"
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        sync_contributions(pool, &self.client, today()).await
+    }
+}
+
+#[derive(Default, Debug)]
+struct DailyCounts {
+    commits: i32,
+    pull_requests: i32,
+    reviews: i32,
+}
+
+/// Sync every current member with a GitHub username. A member that fails doesn't stop the
+/// others, but the run is reported as failed so that it is retried.
+pub async fn sync_contributions(
+    pool: &PgPool,
+    client: &GitHubClient,
+    today: NaiveDate,
+) -> Result<(), String> {
+    let members: Vec<(i32, String)> = sqlx::query_as(
+        "SELECT member_id, github_user FROM Member
+        WHERE github_user IS NOT NULL AND github_user <> ''
+        AND membership_status IN ('Active', 'OnLeave') AND role <> 'Bot'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch members: {}", e))?;
+
+    let mut failed = Vec::new();
+    for (member_id, username) in &members {
+        if let Err(e) = sync_member(pool, client, *member_id, username, today).await {
+            warn!("Failed to sync GitHub contributions of {}: {}", username, e);
+            failed.push(username.as_str());
+        }
+    }
+
+    info!(
+        "Synced GitHub contributions of {} members",
+        members.len() - failed.len()
+    );
+
+    if !failed.is_empty() {
+        return Err(format!("Failed for {}", failed.join(", ")));
+    }
+    Ok(())
+}
+
+async fn sync_member(
+    pool: &PgPool,
+    client: &GitHubClient,
+    member_id: i32,
+    username: &str,
+    today: NaiveDate,
+) -> Result<(), String> {
+    let Some((events, truncated)) = fetch_events(client, username).await? else {
+        warn!("GitHub user {} not found, skipping", username);
+        return Ok(());
+    };
+    let Some((start, counts)) = daily_counts(client.org_name(), &events, truncated, today) else {
+        debug!("{}: no public events", username);
+        return Ok(());
+    };
+
+    debug!(
+        "{}: {} days with contributions since {}",
+        username,
+        counts.len(),
+        start
+    );
+
+    let (dates, commits, pull_requests, reviews) = counts.into_iter().fold(
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+        |mut acc, (date, c)| {
+            acc.0.push(date);
+            acc.1.push(c.commits);
+            acc.2.push(c.pull_requests);
+            acc.3.push(c.reviews);
+            acc
+        },
+    );
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    sqlx::query("DELETE FROM GitHubContribution WHERE member_id = $1 AND date BETWEEN $2 AND $3")
+        .bind(member_id)
+        .bind(start)
+        .bind(today)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to clear contributions: {}", e))?;
+
+    sqlx::query(
+        "INSERT INTO GitHubContribution (member_id, date, commits, pull_requests, reviews)
+        SELECT $1, * FROM UNNEST($2::DATE[], $3::INT[], $4::INT[], $5::INT[])",
+    )
+    .bind(member_id)
+    .bind(dates)
+    .bind(commits)
+    .bind(pull_requests)
+    .bind(reviews)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store contributions: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit contributions: {}", e))
+}
+
+/// The user's public events, newest first, and whether GitHub had more than it serves.
+/// `None` if the user doesn't exist.
+async fn fetch_events(
+    client: &GitHubClient,
+    username: &str,
+) -> Result<Option<(Vec<GitHubEvent>, bool)>, String> {
+    let mut events = Vec::new();
+    let mut truncated = false;
+    for page in 1..=MAX_EVENT_PAGES {
+        let Some(batch) = client
+            .public_events(username, page, EVENTS_PER_PAGE)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let full_page = batch.len() as u32 == EVENTS_PER_PAGE;
+        events.extend(batch);
+        if !full_page {
+            break;
+        }
+        truncated = page == MAX_EVENT_PAGES;
+    }
+
+    Ok(Some((events, truncated)))
+}
+
+/// Contributions to the org's repositories per day, from the first day the events fully
+/// cover up to `today`. `None` if there are no events, and so nothing to go by.
+fn daily_counts(
+    org_name: &str,
+    events: &[GitHubEvent],
+    truncated: bool,
+    today: NaiveDate,
+) -> Option<(NaiveDate, BTreeMap<NaiveDate, DailyCounts>)> {
+    let repo_prefix = format!("{}/", org_name.to_lowercase());
+    let mut counts: BTreeMap<NaiveDate, DailyCounts> = BTreeMap::new();
+    let mut oldest = None;
+    for event in events {
+        let Some(date) = event_date(event) else {
+            continue;
+        };
+        oldest = Some(oldest.map_or(date, |o: NaiveDate| o.min(date)));
+
+        if !event.repo.name.to_lowercase().starts_with(&repo_prefix) {
+            continue;
+        }
+
+        let day = counts.entry(date).or_default();
+        match event.kind.as_str() {
+            "PushEvent" => day.commits += push_commits(event),
+            "PullRequestEvent" if event.payload["action"] == "opened" => day.pull_requests += 1,
+            "PullRequestReviewEvent" => day.reviews += 1,
+            _ => {}
+        }
+    }
+
+    // GitHub may keep less than the documented window, so the events only say anything
+    // about the days since the oldest one. If the list was cut short, that day may be
+    // incomplete too.
+    let oldest = oldest?;
+    let covered = if truncated {
+        oldest + chrono::Duration::days(1)
+    } else {
+        oldest
+    };
+    let start = covered.max(today - chrono::Duration::days(EVENTS_WINDOW_DAYS - 1));
+    counts.retain(|date, _| *date >= start && *date <= today);
+
+    Some((start, counts))
+}
+
+/// The IST date on which an event happened
+fn event_date(event: &GitHubEvent) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(&event.created_at)
+        .ok()
+        .map(|time| time.with_timezone(&Kolkata).date_naive())
+}
+
+/// Number of new commits in a push. Falls back to a single commit when the payload
+/// doesn't say, since a push always has at least one.
+fn push_commits(event: &GitHubEvent) -> i32 {
+    let payload = &event.payload;
+    payload["distinct_size"]
+        .as_i64()
+        .or_else(|| payload["size"].as_i64())
+        .or_else(|| payload["commits"].as_array().map(|c| c.len() as i64))
+        .unwrap_or(1) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use chrono::Duration;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Deserialize)]
+    struct Page {
+        page: u32,
+        per_page: u32,
+    }
+
+    fn date(days_ago: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 31).unwrap() - Duration::days(days_ago)
+    }
+
+    /// An event at noon IST
+    fn event(kind: &str, repo: &str, days_ago: i64) -> Value {
+        json!({
+            "type": kind,
+            "repo": { "name": repo },
+            "created_at": format!("{}T06:30:00Z", date(days_ago)),
+            "payload": { "action": "opened", "distinct_size": 2 },
+        })
+    }
+
+    /// `short` has a few events, `busy` more than GitHub serves, one a day going back, and
+    /// `ghost` doesn't exist
+    async fn events(
+        Path(user): Path<String>,
+        Query(page): Query<Page>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let events: Vec<Value> = match user.as_str() {
+            "short" => vec![
+                event("PushEvent", "amfoss/root", 0),
+                event("PullRequestEvent", "AMFOSS/home", 2),
+                event("PushEvent", "someone/else", 2),
+                event("PullRequestReviewEvent", "amfoss/root", 10),
+                event("WatchEvent", "someone/else", 20),
+            ],
+            "busy" => (0..400)
+                .map(|i| event("PushEvent", "amfoss/root", i / 5))
+                .collect(),
+            _ => return Err(StatusCode::NOT_FOUND),
+        };
+
+        let start = ((page.page - 1) * page.per_page) as usize;
+        let end = (start + page.per_page as usize).min(events.len());
+        Ok(Json(Value::Array(events[start.min(end)..end].to_vec())))
+    }
+
+    async fn mock_client() -> GitHubClient {
+        let app = Router::new().route("/users/{user}/events/public", get(events));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        GitHubClient::new(&format!("http://{}/", addr), "amfoss", None)
+    }
+
+    #[tokio::test]
+    async fn window_starts_at_oldest_event() {
+        let client = mock_client().await;
+
+        let (events, truncated) = fetch_events(&client, "short").await.unwrap().unwrap();
+        assert_eq!(events.len(), 5);
+        assert!(!truncated);
+
+        let (start, counts) = daily_counts(client.org_name(), &events, truncated, date(0)).unwrap();
+        assert_eq!(start, date(20));
+        assert_eq!(
+            counts.keys().copied().collect::<Vec<_>>(),
+            vec![date(10), date(2), date(0)]
+        );
+        assert_eq!(counts[&date(0)].commits, 2);
+        assert_eq!(counts[&date(2)].pull_requests, 1);
+        assert_eq!(counts[&date(2)].commits, 0);
+        assert_eq!(counts[&date(10)].reviews, 1);
+    }
+
+    #[tokio::test]
+    async fn truncated_feed_skips_oldest_day() {
+        let client = mock_client().await;
+
+        let (events, truncated) = fetch_events(&client, "busy").await.unwrap().unwrap();
+        assert_eq!(events.len(), (MAX_EVENT_PAGES * EVENTS_PER_PAGE) as usize);
+        assert!(truncated);
+
+        // 300 events at 5 a day reach back 59 days
+        let (start, counts) = daily_counts(client.org_name(), &events, truncated, date(0)).unwrap();
+        assert_eq!(start, date(58));
+        assert_eq!(counts.len(), 59);
+        assert!(counts.values().all(|day| day.commits == 10));
+    }
+
+    #[tokio::test]
+    async fn window_is_capped_and_empty_feeds_are_skipped() {
+        let client = mock_client().await;
+
+        let (events, truncated) = fetch_events(&client, "short").await.unwrap().unwrap();
+        let (start, counts) =
+            daily_counts(client.org_name(), &events, truncated, date(-80)).unwrap();
+        // 80 days on, the window starts 9 days before the last contribution
+        assert_eq!(start, date(9));
+        assert_eq!(
+            counts.keys().copied().collect::<Vec<_>>(),
+            vec![date(2), date(0)]
+        );
+
+        assert!(daily_counts(client.org_name(), &[], false, date(0)).is_none());
+        assert!(fetch_events(&client, "ghost").await.unwrap().is_none());
+    }
+}
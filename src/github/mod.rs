@@ -0,0 +1,168 @@
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+
+pub mod contributions;
+pub mod org_sync;
+
+pub use contributions::GitHubContributionJob;
+pub use org_sync::GitHubOrgSyncJob;
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Base URL of the GitHub REST API. Set `GITHUB_API_URL` to point Root at a local mock server.
+pub fn api_url_from_env() -> String {
+    env::var("GITHUB_API_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| DEFAULT_API_URL.to_string())
+}
+
+/// Client for the parts of the GitHub API used by background jobs. Requests are made with
+/// `GITHUB_TOKEN` when it is set, since unauthenticated requests are limited to 60 an hour.
+#[derive(Clone, Debug)]
+pub struct GitHubClient {
+    api_url: String,
+    org_name: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub repo: GitHubEventRepo,
+    pub created_at: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubAccount {
+    pub id: i64,
+    pub login: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubEventRepo {
+    /// `owner/name`
+    pub name: String,
+}
+
+impl GitHubClient {
+    pub fn new(api_url: &str, org_name: &str, token: Option<String>) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            org_name: org_name.to_string(),
+            token,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            &api_url_from_env(),
+            &env::var("GITHUB_ORG_NAME").unwrap_or_else(|_| "amfoss".to_string()),
+            env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty()),
+        )
+    }
+
+    pub fn org_name(&self) -> &str {
+        &self.org_name
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// GET a path relative to the API URL. Returns `None` on a 404.
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, String> {
+        let mut request = self
+            .http
+            .get(format!("{}{}", self.api_url, path))
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "Root-Backend");
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch {}: {}", path, e))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .json()
+                .await
+                .map(Some)
+                .map_err(|e| format!("Failed to parse {}: {}", path, e)),
+            status => Err(format!("GitHub returned {} for {}", status, path)),
+        }
+    }
+
+    /// Every item of a paginated list. `None` on a 404.
+    async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<Vec<T>>, String> {
+        const PER_PAGE: usize = 100;
+
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        for page in 1.. {
+            let Some(batch) = self
+                .get::<Vec<T>>(&format!(
+                    "{}{}per_page={}&page={}",
+                    path, separator, PER_PAGE, page
+                ))
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            let last_page = batch.len() < PER_PAGE;
+            items.extend(batch);
+            if last_page {
+                break;
+            }
+        }
+
+        Ok(Some(items))
+    }
+
+    /// Every member of the org. Private memberships are only listed with a token of an org member.
+    pub async fn org_members(&self) -> Result<Vec<GitHubAccount>, String> {
+        self.get_all_pages(&format!("/orgs/{}/members", self.org_name))
+            .await?
+            .ok_or_else(|| format!("Organization {} not found", self.org_name))
+    }
+
+    /// Every member of a team in the org. `None` if the team doesn't exist.
+    pub async fn team_members(
+        &self,
+        team_slug: &str,
+    ) -> Result<Option<Vec<GitHubAccount>>, String> {
+        self.get_all_pages(&format!(
+            "/orgs/{}/teams/{}/members",
+            self.org_name, team_slug
+        ))
+        .await
+    }
+
+    /// One page of a user's public events, newest first. `None` if the user doesn't exist.
+    pub async fn public_events(
+        &self,
+        username: &str,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Option<Vec<GitHubEvent>>, String> {
+        self.get(&format!(
+            "/users/{}/events/public?per_page={}&page={}",
+            username, per_page, page
+        ))
+        .await
+    }
+}
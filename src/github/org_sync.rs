@@ -0,0 +1,303 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+
+use crate::auth::session::SessionService;
+use crate::github::GitHubClient;
+use crate::models::github::{GitHubOrgSync, GitHubTeamMapping, OrgSyncDifferenceKind};
+use crate::scheduler::Job;
+
+/// Reconciles members against the GitHub org: revokes the sessions of members who left it,
+/// applies track and group assignments from mapped teams, and records every difference
+/// for admins in `GitHubOrgSyncDifference`.
+pub struct GitHubOrgSyncJob {
+    pub client: GitHubClient,
+}
+
+#[async_trait]
+impl Job for GitHubOrgSyncJob {
+    fn name(&self) -> &'static str {
+        "github_org_sync"
+    }
+
+    fn schedule(&self) -> &'static str {
+        "20 */6 * * *"
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        // Without a token only public memberships are listed, and everyone else would look
+        // like they left the org.
+        if !self.client.has_token() {
+            warn!("GITHUB_TOKEN is not set, skipping org sync");
+            return Ok(());
+        }
+
+        sync_org(pool, &self.client).await.map(|_| ())
+    }
+}
+
+struct Difference {
+    kind: OrgSyncDifferenceKind,
+    member_id: Option<i32>,
+    github_user: String,
+    detail: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct LinkedMember {
+    member_id: i32,
+    github_user: String,
+    track_id: Option<i32>,
+    group_id: Option<i32>,
+    is_current: bool,
+}
+
+/// Follow renamed accounts by their GitHub ID, and store the ID of members who only have a
+/// login, so that a rename isn't mistaken for leaving the org.
+async fn update_github_accounts(pool: &PgPool, ids: &[i64], logins: &[&str]) -> Result<(), String> {
+    let renamed = sqlx::query(
+        "UPDATE Member m SET github_user = a.login
+        FROM UNNEST($1::BIGINT[], $2::TEXT[]) AS a(id, login)
+        WHERE m.github_id = a.id AND m.github_user IS DISTINCT FROM a.login",
+    )
+    .bind(ids)
+    .bind(logins)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update renamed accounts: {}", e))?
+    .rows_affected();
+
+    // A login shared by several members is ambiguous and left for them to log in
+    let linked = sqlx::query(
+        "UPDATE Member m SET github_id = a.id
+        FROM UNNEST($1::BIGINT[], $2::TEXT[]) AS a(id, login)
+        WHERE m.github_id IS NULL AND LOWER(m.github_user) = LOWER(a.login) AND m.role <> 'Bot'
+        AND NOT EXISTS (SELECT 1 FROM Member o WHERE o.github_id = a.id)
+        AND NOT EXISTS (
+            SELECT 1 FROM Member o
+            WHERE o.member_id <> m.member_id AND LOWER(o.github_user) = LOWER(m.github_user)
+        )",
+    )
+    .bind(ids)
+    .bind(logins)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to store GitHub IDs: {}", e))?
+    .rows_affected();
+
+    if renamed > 0 || linked > 0 {
+        info!(
+            "GitHub org sync: {} renamed accounts, {} GitHub IDs stored",
+            renamed, linked
+        );
+    }
+    Ok(())
+}
+
+pub async fn sync_org(pool: &PgPool, client: &GitHubClient) -> Result<GitHubOrgSync, String> {
+    // Fetch everything from GitHub first, so that an API failure doesn't leave a half-applied sync.
+    let org_members = client.org_members().await?;
+    if org_members.is_empty() {
+        return Err("GitHub returned no org members, refusing to sync".to_string());
+    }
+    let org_logins: HashSet<String> = org_members
+        .iter()
+        .map(|account| account.login.to_lowercase())
+        .collect();
+
+    let mappings = sqlx::query_as::<_, GitHubTeamMapping>(
+        "SELECT * FROM GitHubTeamMapping ORDER BY team_slug",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch team mappings: {}", e))?;
+
+    let mut teams = Vec::new();
+    for mapping in mappings {
+        match client.team_members(&mapping.team_slug).await? {
+            Some(accounts) => {
+                let logins: HashSet<String> = accounts
+                    .into_iter()
+                    .map(|a| a.login.to_lowercase())
+                    .collect();
+                teams.push((mapping, logins));
+            }
+            None => warn!("GitHub team {} not found, skipping", mapping.team_slug),
+        }
+    }
+
+    let (ids, logins): (Vec<i64>, Vec<&str>) = org_members
+        .iter()
+        .map(|account| (account.id, account.login.as_str()))
+        .unzip();
+    update_github_accounts(pool, &ids, &logins).await?;
+
+    let members = sqlx::query_as::<_, LinkedMember>(
+        "SELECT member_id, github_user, track_id, group_id,
+            membership_status IN ('Active', 'OnLeave', 'Suspended') AS is_current
+        FROM Member
+        WHERE github_user IS NOT NULL AND github_user <> '' AND role <> 'Bot'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch members: {}", e))?;
+
+    let sync_id: i32 =
+        sqlx::query_scalar("INSERT INTO GitHubOrgSync (org_members) VALUES ($1) RETURNING sync_id")
+            .bind(org_members.len() as i32)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to record sync: {}", e))?;
+
+    let mut differences = Vec::new();
+    let mut sessions_revoked = 0;
+
+    // Alumni are expected to leave the org, so only current members are reported.
+    for member in members.iter().filter(|m| m.is_current) {
+        if org_logins.contains(&member.github_user.to_lowercase()) {
+            continue;
+        }
+
+        let revoked = SessionService::revoke_member_sessions(pool, member.member_id).await?;
+        sessions_revoked += revoked;
+        differences.push(Difference {
+            kind: OrgSyncDifferenceKind::LeftOrg,
+            member_id: Some(member.member_id),
+            github_user: member.github_user.clone(),
+            detail: Some(format!("{} sessions revoked", revoked)),
+        });
+    }
+
+    let known: HashMap<String, &LinkedMember> = members
+        .iter()
+        .map(|m| (m.github_user.to_lowercase(), m))
+        .collect();
+
+    let mut unregistered: Vec<&str> = org_members
+        .iter()
+        .filter(|account| !known.contains_key(&account.login.to_lowercase()))
+        .map(|account| account.login.as_str())
+        .collect();
+    unregistered.sort_unstable();
+    differences.extend(unregistered.into_iter().map(|login| Difference {
+        kind: OrgSyncDifferenceKind::NotRegistered,
+        member_id: None,
+        github_user: login.to_string(),
+        detail: None,
+    }));
+
+    // A member in several mapped teams ends up with the assignment of the last team by slug.
+    let mut assigned: HashMap<i32, (Option<i32>, Option<i32>)> = HashMap::new();
+    for (mapping, logins) in &teams {
+        for login in logins {
+            let Some(member) = known.get(login).filter(|m| m.is_current) else {
+                continue;
+            };
+            let entry = assigned
+                .entry(member.member_id)
+                .or_insert((member.track_id, member.group_id));
+
+            if let Some(track_id) = mapping.track_id.filter(|t| entry.0 != Some(*t)) {
+                differences.push(Difference {
+                    kind: OrgSyncDifferenceKind::TrackChanged,
+                    member_id: Some(member.member_id),
+                    github_user: member.github_user.clone(),
+                    detail: Some(format!(
+                        "Team {}: track {} -> {}",
+                        mapping.team_slug,
+                        entry.0.map_or("none".to_string(), |t| t.to_string()),
+                        track_id
+                    )),
+                });
+                entry.0 = Some(track_id);
+            }
+
+            if let Some(group_id) = mapping.group_id.filter(|g| entry.1 != Some(*g)) {
+                differences.push(Difference {
+                    kind: OrgSyncDifferenceKind::GroupChanged,
+                    member_id: Some(member.member_id),
+                    github_user: member.github_user.clone(),
+                    detail: Some(format!(
+                        "Team {}: group {} -> {}",
+                        mapping.team_slug,
+                        entry.1.map_or("none".to_string(), |g| g.to_string()),
+                        group_id
+                    )),
+                });
+                entry.1 = Some(group_id);
+            }
+        }
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let (member_ids, track_ids, group_ids): (Vec<i32>, Vec<Option<i32>>, Vec<Option<i32>>) =
+        assigned
+            .into_iter()
+            .map(|(member_id, (track_id, group_id))| (member_id, track_id, group_id))
+            .fold((Vec::new(), Vec::new(), Vec::new()), |mut acc, row| {
+                acc.0.push(row.0);
+                acc.1.push(row.1);
+                acc.2.push(row.2);
+                acc
+            });
+
+    sqlx::query(
+        "UPDATE Member m SET track_id = a.track_id, group_id = a.group_id
+        FROM UNNEST($1::INT[], $2::INT[], $3::INT[]) AS a(member_id, track_id, group_id)
+        WHERE m.member_id = a.member_id
+        AND (m.track_id IS DISTINCT FROM a.track_id OR m.group_id IS DISTINCT FROM a.group_id)",
+    )
+    .bind(member_ids)
+    .bind(track_ids)
+    .bind(group_ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to apply team assignments: {}", e))?;
+
+    let mut query = sqlx::QueryBuilder::new(
+        "INSERT INTO GitHubOrgSyncDifference (sync_id, kind, member_id, github_user, detail) ",
+    );
+    if !differences.is_empty() {
+        query.push_values(&differences, |mut row, difference| {
+            row.push_bind(sync_id)
+                .push_bind(difference.kind)
+                .push_bind(difference.member_id)
+                .push_bind(&difference.github_user)
+                .push_bind(&difference.detail);
+        });
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to record differences: {}", e))?;
+    }
+
+    let sync = sqlx::query_as::<_, GitHubOrgSync>(
+        "UPDATE GitHubOrgSync SET sessions_revoked = $2, finished_at = NOW()
+        WHERE sync_id = $1
+        RETURNING *",
+    )
+    .bind(sync_id)
+    .bind(sessions_revoked as i32)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to record sync: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit sync: {}", e))?;
+
+    info!(
+        "GitHub org sync: {} org members, {} differences, {} sessions revoked",
+        org_members.len(),
+        differences.len(),
+        sessions_revoked
+    );
+
+    Ok(sync)
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo,
+};
+use async_graphql::{ServerError, ServerResult, Value};
+
+use crate::auth::AuthContext;
+
+/// The only mutation allowed while impersonating
+const EXIT_MUTATION: &str = "stopImpersonation";
+
+/// Makes impersonation sessions read-only, so admins can see what a member sees without
+/// acting as them. Every mutation except `stopImpersonation` is rejected.
+pub struct ReadOnlyImpersonation;
+
+impl ExtensionFactory for ReadOnlyImpersonation {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ReadOnlyImpersonationExtension)
+    }
+}
+
+struct ReadOnlyImpersonationExtension;
+
+#[async_trait::async_trait]
+impl Extension for ReadOnlyImpersonationExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let impersonating = ctx
+            .data_opt::<AuthContext>()
+            .is_some_and(|auth| auth.is_impersonating());
+
+        if impersonating && info.parent_type == "Mutation" && info.name != EXIT_MUTATION {
+            return Err(ServerError::new(
+                "Mutations are disabled while impersonating, use stopImpersonation first",
+                None,
+            ));
+        }
+
+        next.run(ctx, info).await
+    }
+}
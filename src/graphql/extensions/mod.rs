@@ -0,0 +1,5 @@
+pub mod impersonation;
+pub mod persisted_queries;
+
+pub use impersonation::ReadOnlyImpersonation;
+pub use persisted_queries::PersistedQueries;
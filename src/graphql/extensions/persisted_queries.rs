@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest,
+};
+use async_graphql::{ErrorExtensionValues, Request, ServerError, ServerResult};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{debug, info};
+
+/// Queries registered through APQ are kept until this many are cached, then dropped
+const MAX_CACHED_QUERIES: usize = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQuery {
+    version: i32,
+    sha256_hash: String,
+}
+
+/// `@apollo/generate-persisted-query-manifest` output
+#[derive(Deserialize)]
+struct ApolloManifest {
+    operations: Vec<ApolloManifestOperation>,
+}
+
+#[derive(Deserialize)]
+struct ApolloManifestOperation {
+    id: String,
+    body: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    Apollo(ApolloManifest),
+    /// Query texts keyed by their hash
+    Map(HashMap<String, String>),
+}
+
+/// Where registered operations come from
+enum Allowlist {
+    Manifest(HashMap<String, String>),
+    /// `PersistedOperation`, queried on every miss so new rows apply without a restart
+    Database(Arc<PgPool>),
+}
+
+fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+fn error(message: &str, code: &str) -> ServerError {
+    let mut extensions = ErrorExtensionValues::default();
+    extensions.set("code", code);
+
+    let mut error = ServerError::new(message, None);
+    error.extensions = Some(extensions);
+    error
+}
+
+/// Automatic Persisted Queries, and optionally an allowlist of operations. Clients can
+/// send the hash of a query instead of its text once the server knows it, either from an
+/// earlier request or the allowlist. When the allowlist is enforced, only its operations
+/// run, whether sent by hash or in full.
+pub struct PersistedQueries(Arc<PersistedQueriesState>);
+
+struct PersistedQueriesState {
+    allowlist: Option<Allowlist>,
+    enforce: bool,
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl PersistedQueries {
+    /// Configured by `GRAPHQL_ALLOWLIST`, either a path to a manifest file or `db`. The
+    /// allowlist is only enforced outside development, where unlisted operations are
+    /// logged instead.
+    pub fn from_env(pool: Arc<PgPool>, env: &str) -> Result<Self, String> {
+        let allowlist = match std::env::var("GRAPHQL_ALLOWLIST")
+            .unwrap_or_default()
+            .as_str()
+        {
+            "" => None,
+            "db" => Some(Allowlist::Database(pool)),
+            path => Some(Allowlist::Manifest(Self::load_manifest(path)?)),
+        };
+
+        let enforce = allowlist.is_some() && env != "development";
+        match &allowlist {
+            Some(Allowlist::Manifest(operations)) => info!(
+                "Loaded {} allowed operations, enforced: {}",
+                operations.len(),
+                enforce
+            ),
+            Some(Allowlist::Database(_)) => {
+                info!(
+                    "Allowing operations from the database, enforced: {}",
+                    enforce
+                )
+            }
+            None => {}
+        }
+
+        Ok(Self(Arc::new(PersistedQueriesState {
+            allowlist,
+            enforce,
+            cache: Mutex::new(HashMap::new()),
+        })))
+    }
+
+    fn load_manifest(path: &str) -> Result<HashMap<String, String>, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read operation manifest {}: {}", path, e))?;
+        let manifest: Manifest = serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid operation manifest {}: {}", path, e))?;
+
+        let operations: HashMap<String, String> = match manifest {
+            Manifest::Apollo(manifest) => manifest
+                .operations
+                .into_iter()
+                .map(|operation| (operation.id, operation.body))
+                .collect(),
+            Manifest::Map(operations) => operations,
+        };
+
+        match operations
+            .iter()
+            .find(|(hash, query)| sha256(query) != **hash)
+        {
+            Some((hash, _)) => Err(format!(
+                "Operation {} in {} doesn't match its hash",
+                hash, path
+            )),
+            None => Ok(operations),
+        }
+    }
+}
+
+impl PersistedQueriesState {
+    /// The allowed query with this hash
+    async fn allowed_query(&self, hash: &str) -> ServerResult<Option<String>> {
+        match &self.allowlist {
+            None => Ok(None),
+            Some(Allowlist::Manifest(operations)) => Ok(operations.get(hash).cloned()),
+            Some(Allowlist::Database(pool)) => {
+                sqlx::query_scalar("SELECT query FROM PersistedOperation WHERE operation_hash = $1")
+                    .bind(hash)
+                    .fetch_optional(pool.as_ref())
+                    .await
+                    .map_err(|e| {
+                        ServerError::new(format!("Failed to query allowlist: {}", e), None)
+                    })
+            }
+        }
+    }
+
+    fn cached_query(&self, hash: &str) -> Option<String> {
+        self.cache
+            .lock()
+            .expect("Query cache lock poisoned")
+            .get(hash)
+            .cloned()
+    }
+
+    fn cache_query(&self, hash: String, query: String) {
+        let mut cache = self.cache.lock().expect("Query cache lock poisoned");
+        if cache.len() >= MAX_CACHED_QUERIES {
+            cache.clear();
+        }
+        cache.insert(hash, query);
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension(self.0.clone()))
+    }
+}
+
+struct PersistedQueriesExtension(Arc<PersistedQueriesState>);
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let state = &self.0;
+
+        let persisted = match request.extensions.remove("persistedQuery") {
+            Some(value) => {
+                let persisted: PersistedQuery = serde_json::to_value(value)
+                    .and_then(serde_json::from_value)
+                    .map_err(|_| ServerError::new("Invalid persistedQuery extension", None))?;
+                if persisted.version != 1 {
+                    return Err(ServerError::new(
+                        "Only version 1 of persisted queries is supported",
+                        None,
+                    ));
+                }
+                Some(persisted.sha256_hash)
+            }
+            None => None,
+        };
+
+        // Whether the operation is known to be in the allowlist
+        let mut listed = false;
+        let hash = match persisted {
+            Some(hash) if request.query.is_empty() => {
+                let query = match state.allowed_query(&hash).await? {
+                    Some(query) => {
+                        listed = true;
+                        Some(query)
+                    }
+                    None if !state.enforce => state.cached_query(&hash),
+                    None => None,
+                };
+                request.query = query
+                    .ok_or_else(|| error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"))?;
+                hash
+            }
+            Some(hash) => {
+                if sha256(&request.query) != hash {
+                    return Err(ServerError::new(
+                        "The persisted query hash doesn't match the query",
+                        None,
+                    ));
+                }
+                if !state.enforce {
+                    state.cache_query(hash.clone(), request.query.clone());
+                }
+                hash
+            }
+            None => sha256(&request.query),
+        };
+
+        if state.allowlist.is_some() && !listed && state.allowed_query(&hash).await?.is_none() {
+            if state.enforce {
+                return Err(error(
+                    "This operation is not allowed",
+                    "OPERATION_NOT_ALLOWED",
+                ));
+            }
+            debug!("Operation {} is not in the allowlist", hash);
+        }
+
+        next.run(ctx, request).await
+    }
+}
@@ -0,0 +1,186 @@
+use async_graphql::{Context, Result};
+use chrono::NaiveDate;
+
+const DEFAULT_MAX_DEPTH: usize = 15;
+/// About twice the cost of the most expensive dashboard query, a month of attendance and
+/// status records for every member (5,550). A year of records for every member is rejected.
+const DEFAULT_MAX_COMPLEXITY: usize = 12_000;
+const DEFAULT_MAX_DATE_RANGE_DAYS: i64 = 366;
+
+/// Complexity of a member list relative to one member, since members are listed in full
+pub const MEMBER_LIST_COST: usize = 25;
+/// Complexity of resolvers that scan a member's whole history, like `streak`
+pub const HISTORY_SCAN_COST: usize = 10;
+/// Complexity of resolvers that aggregate over a date range, like `absentCount`
+pub const AGGREGATE_COST: usize = 5;
+
+/// Limits on the size of GraphQL queries. Each field costs 1 unless annotated otherwise, and
+/// lists multiply the cost of their fields. The depth and cost of each query are returned
+/// in the `analyzer` response extension.
+#[derive(Clone, Copy, Debug)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+    /// Longest range `records` and other date range arguments can span
+    pub max_date_range_days: i64,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_complexity: DEFAULT_MAX_COMPLEXITY,
+            max_date_range_days: DEFAULT_MAX_DATE_RANGE_DAYS,
+        }
+    }
+}
+
+impl QueryLimits {
+    /// Configured by `GRAPHQL_MAX_DEPTH`, `GRAPHQL_MAX_COMPLEXITY` and
+    /// `GRAPHQL_MAX_DATE_RANGE_DAYS`, using the defaults for unset ones.
+    pub fn from_env() -> Result<Self, String> {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .map_err(|_| format!("{} must be a positive number", name)),
+                Err(_) => Ok(default),
+            }
+        }
+
+        let defaults = Self::default();
+        Ok(Self {
+            max_depth: var("GRAPHQL_MAX_DEPTH", defaults.max_depth)?,
+            max_complexity: var("GRAPHQL_MAX_COMPLEXITY", defaults.max_complexity)?,
+            max_date_range_days: var("GRAPHQL_MAX_DATE_RANGE_DAYS", defaults.max_date_range_days)?,
+        })
+    }
+}
+
+/// Complexity of a field returning up to one row per day from `start_date` to `end_date`
+pub fn date_range_cost(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    child_complexity: usize,
+) -> usize {
+    let days = (end_date - start_date).num_days().max(0) as usize + 1;
+    days.saturating_mul(child_complexity)
+}
+
+/// Reject ranges that end before they start or are longer than `max_date_range_days`
+pub fn check_date_range(
+    ctx: &Context<'_>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<()> {
+    let limits = ctx
+        .data::<QueryLimits>()
+        .expect("QueryLimits must be in context.");
+
+    if end_date < start_date {
+        return Err("end_date must be >= start_date".into());
+    }
+    if (end_date - start_date).num_days() >= limits.max_date_range_days {
+        return Err(format!(
+            "Date ranges can be at most {} days long",
+            limits.max_date_range_days
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{Mutation, Query, Subscription};
+    use async_graphql::extensions::Analyzer;
+    use async_graphql::Schema;
+
+    /// The widest queries of the dashboards, over the longest ranges they ask for
+    const FRONTEND_QUERIES: [&str; 4] = [
+        // Attendance dashboard, a month at a time
+        "{ allMembers { memberId name year attendance {
+            records(startDate: \"2026-10-01\", endDate: \"2026-10-31\") {
+                date isPresent timeIn timeOut
+            }
+            presentCount(startDate: \"2026-10-01\", endDate: \"2026-10-31\")
+            absentCount(startDate: \"2026-10-01\", endDate: \"2026-10-31\")
+        } } }",
+        // Status update dashboard, a month at a time
+        "{ allMembers { memberId name year status {
+            records(startDate: \"2026-10-01\", endDate: \"2026-10-31\") {
+                date isSent onBreak
+            }
+            streak { currentStreak maxStreak }
+            updateCount(startDate: \"2026-10-01\", endDate: \"2026-10-31\")
+        } } }",
+        // Both for every member, the most expensive of them
+        "{ allMembers { memberId name year
+            attendance {
+                records(startDate: \"2026-10-01\", endDate: \"2026-10-31\") {
+                    date isPresent timeIn timeOut
+                }
+            }
+            status {
+                records(startDate: \"2026-10-01\", endDate: \"2026-10-31\") {
+                    date isSent onBreak
+                }
+            }
+        } }",
+        // Profile heatmaps, a year of one member
+        "{ member(memberId: 1) { memberId name year
+            attendance { records(startDate: \"2025-11-01\", endDate: \"2026-10-31\") { date isPresent } }
+            status {
+                records(startDate: \"2025-11-01\", endDate: \"2026-10-31\") { date isSent }
+                streak { currentStreak maxStreak }
+            }
+            github { contributions(from: \"2025-11-01\", to: \"2026-10-31\") {
+                date commits pullRequests reviews
+            } }
+        } }",
+    ];
+
+    /// Without an auth context, queries that pass the limits fail in their guards instead
+    async fn errors(query: &str) -> Vec<String> {
+        let limits = QueryLimits::default();
+        let schema = Schema::build(Query::default(), Mutation::default(), Subscription)
+            .data(limits)
+            .extension(Analyzer)
+            .limit_depth(limits.max_depth)
+            .limit_complexity(limits.max_complexity)
+            .finish();
+
+        schema
+            .execute(query)
+            .await
+            .errors
+            .into_iter()
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn frontend_queries_pass() {
+        for query in FRONTEND_QUERIES {
+            let errors = errors(query).await;
+            assert!(
+                errors.iter().all(|e| e.starts_with("Authentication")),
+                "{:?} for {}",
+                errors,
+                query
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn a_year_of_every_member_is_too_complex() {
+        let errors = errors(
+            "{ allMembers { memberId name year status {
+                records(startDate: \"2025-11-01\", endDate: \"2026-10-31\") { date isSent }
+            } } }",
+        )
+        .await;
+        assert_eq!(errors, vec!["Query is too complex.".to_string()]);
+    }
+}
@@ -1,12 +1,32 @@
 use async_graphql::MergedObject;
-use mutations::{AttendanceMutations, AuthMutations, MemberMutations, StatusMutations};
-use queries::MemberQueries;
+use mutations::{
+    AttendanceMutations, AuthMutations, DailyTaskMutations, GitHubMutations, GroupMutations,
+    MemberMutations, ProjectMutations, StatusMutations, WebhookMutations,
+};
+use queries::{
+    AuditQueries, GitHubQueries, GroupQueries, MemberQueries, ProjectQueries, SchedulerQueries,
+    StatusQueries, WebhookQueries,
+};
 
+pub mod extensions;
+pub mod limits;
 pub mod mutations;
 pub mod queries;
+pub mod subscriptions;
+
+pub use subscriptions::Subscription;
 
 #[derive(MergedObject, Default)]
-pub struct Query(MemberQueries);
+pub struct Query(
+    MemberQueries,
+    GroupQueries,
+    ProjectQueries,
+    StatusQueries,
+    SchedulerQueries,
+    GitHubQueries,
+    AuditQueries,
+    WebhookQueries,
+);
 
 #[derive(MergedObject, Default)]
 pub struct Mutation(
@@ -14,4 +34,9 @@ pub struct Mutation(
     AttendanceMutations,
     StatusMutations,
     AuthMutations,
+    DailyTaskMutations,
+    GroupMutations,
+    ProjectMutations,
+    GitHubMutations,
+    WebhookMutations,
 );
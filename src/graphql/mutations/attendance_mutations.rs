@@ -6,8 +6,14 @@ use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use sqlx::PgPool;
 
-use crate::auth::guards::AdminOrBotGuard;
-use crate::models::attendance::{AttendanceRecord, MarkAttendanceInput};
+use crate::auth::guards::{AdminOrBotGuard, PermissionGuard};
+use crate::auth::AuthContext;
+use crate::events::{Event, EventBus};
+use crate::models::attendance::{AttendanceRecord, CorrectAttendanceInput, MarkAttendanceInput};
+use crate::models::auth::Permission;
+use crate::models::member::Member;
+use crate::models::webhook::WebhookEvent;
+use crate::webhooks::WebhookService;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -59,6 +65,91 @@ impl AttendanceMutations {
         .fetch_one(pool.as_ref())
         .await?;
 
+        publish_attendance(ctx, &attendance).await;
         Ok(attendance)
     }
+
+    /// Fix a member's attendance on a day, e.g. when the presence bot missed them. Needs
+    /// `attendance.correct` over the member.
+    #[graphql(
+        name = "correctAttendance",
+        guard = "PermissionGuard(\"attendance.correct\")"
+    )]
+    async fn correct_attendance(
+        &self,
+        ctx: &Context<'_>,
+        input: CorrectAttendanceInput,
+    ) -> Result<AttendanceRecord> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let member = sqlx::query_as::<_, Member>("SELECT * FROM Member WHERE member_id = $1")
+            .bind(input.member_id)
+            .fetch_optional(pool.as_ref())
+            .await?
+            .ok_or("Member not found")?;
+
+        if !auth.has_permission_over(Permission::AttendanceCorrect, &member) {
+            return Err("You can't correct this member's attendance".into());
+        }
+
+        let (time_in, time_out) = if input.is_present {
+            match (input.time_in, input.time_out) {
+                (Some(time_in), Some(time_out)) => (Some(time_in), Some(time_out)),
+                _ => return Err("timeIn and timeOut are required when present".into()),
+            }
+        } else {
+            (None, None)
+        };
+
+        let attendance = sqlx::query_as::<_, AttendanceRecord>(
+            "INSERT INTO Attendance (member_id, date, is_present, time_in, time_out)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (member_id, date) DO UPDATE SET
+                is_present = EXCLUDED.is_present,
+                time_in = EXCLUDED.time_in,
+                time_out = EXCLUDED.time_out
+            RETURNING *",
+        )
+        .bind(input.member_id)
+        .bind(input.date)
+        .bind(input.is_present)
+        .bind(time_in)
+        .bind(time_out)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        publish_attendance(ctx, &attendance).await;
+        Ok(attendance)
+    }
+}
+
+/// Tell subscriptions and webhooks about a marked attendance record
+async fn publish_attendance(ctx: &Context<'_>, attendance: &AttendanceRecord) {
+    let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+    WebhookService::try_enqueue(
+        pool.as_ref(),
+        WebhookEvent::AttendanceMarked,
+        serde_json::json!({
+            "attendanceId": attendance.attendance_id,
+            "memberId": attendance.member_id,
+            "date": attendance.date,
+            "isPresent": attendance.is_present,
+            "timeIn": attendance.time_in,
+            "timeOut": attendance.time_out,
+        }),
+    )
+    .await;
+
+    let bus = ctx
+        .data::<EventBus>()
+        .expect("EventBus must be in context.");
+    bus.publish(Event::AttendanceMarked {
+        attendance_id: attendance.attendance_id,
+        member_id: attendance.member_id,
+        date: attendance.date,
+    })
+    .await;
 }
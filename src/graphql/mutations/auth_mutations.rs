@@ -1,10 +1,22 @@
 use crate::auth::api_key::ApiKeyService;
-use crate::auth::guards::AdminGuard;
+use crate::auth::guards::{AdminGuard, AuthGuard};
+use crate::auth::invite::InviteService;
+use crate::auth::magic_link::MagicLinkService;
+use crate::auth::permissions::PermissionService;
+use crate::auth::session::{
+    session_cookie, SessionService, IMPERSONATION_DURATION_HOURS, SESSION_DURATION_DAYS,
+};
 use crate::auth::AuthContext;
-use crate::models::auth::ApiKeyResponse;
+use crate::models::auth::{
+    AccessRole, ApiKeyResponse, MemberAccessRole, MemberInviteResponse, Role,
+};
+use crate::models::member::Member;
+use crate::Config;
 use async_graphql::{Context, Object, Result};
+use axum::http::header::SET_COOKIE;
 use sqlx::PgPool;
 use std::sync::Arc;
+use tracing::info;
 
 #[derive(Default)]
 pub struct AuthMutations;
@@ -31,4 +43,199 @@ impl AuthMutations {
 
         Ok(ApiKeyResponse { api_key })
     }
+
+    /// Create a one-time code that links the next GitHub login using it to this member
+    /// (Admin only)
+    #[graphql(name = "createMemberInvite", guard = "AdminGuard")]
+    async fn create_member_invite(
+        &self,
+        ctx: &Context<'_>,
+        member_id: i32,
+        #[graphql(default = 7)] expires_in_days: i32,
+    ) -> Result<MemberInviteResponse> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        if !(1..=30).contains(&expires_in_days) {
+            return Err("expiresInDays must be between 1 and 30".into());
+        }
+
+        let (invite, code) = InviteService::create_invite(
+            pool.as_ref(),
+            member_id,
+            admin_member.member_id,
+            expires_in_days as i64,
+        )
+        .await?;
+
+        Ok(MemberInviteResponse { invite, code })
+    }
+
+    /// Give a member an access role, optionally scoped to a track or group (Admin only)
+    #[graphql(name = "assignAccessRole", guard = "AdminGuard")]
+    async fn assign_access_role(
+        &self,
+        ctx: &Context<'_>,
+        member_id: i32,
+        role: AccessRole,
+        track_id: Option<i32>,
+        group_id: Option<i32>,
+    ) -> Result<MemberAccessRole> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        let assignment = PermissionService::assign_role(
+            pool.as_ref(),
+            member_id,
+            role,
+            track_id,
+            group_id,
+            admin_member.member_id,
+        )
+        .await?;
+
+        Ok(assignment)
+    }
+
+    /// Remove an access role assignment (Admin only)
+    #[graphql(name = "revokeAccessRole", guard = "AdminGuard")]
+    async fn revoke_access_role(&self, ctx: &Context<'_>, assignment_id: i32) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        if !PermissionService::revoke_role(pool.as_ref(), assignment_id).await? {
+            return Err("Access role assignment not found".into());
+        }
+        Ok(true)
+    }
+
+    /// Log in as another member to see what they see, until `stopImpersonation` or for an
+    /// hour. Mutations are disabled meanwhile. Admins and bots can't be impersonated.
+    /// (Admin only)
+    #[graphql(name = "startImpersonation", guard = "AdminGuard")]
+    async fn start_impersonation(&self, ctx: &Context<'_>, member_id: i32) -> Result<Member> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let config = ctx.data::<Config>().expect("Config must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        let member = sqlx::query_as::<_, Member>(
+            "SELECT * FROM Member WHERE member_id = $1 AND membership_status <> 'Suspended'",
+        )
+        .bind(member_id)
+        .fetch_optional(pool.as_ref())
+        .await?
+        .ok_or("Member not found, or suspended")?;
+
+        if member.role != Role::Member {
+            return Err("Only regular members can be impersonated".into());
+        }
+
+        let token = SessionService::create_impersonation_session(
+            pool.as_ref(),
+            member.member_id,
+            admin_member.member_id,
+        )
+        .await?;
+
+        let cookie = session_cookie(
+            config,
+            token,
+            time::Duration::hours(IMPERSONATION_DURATION_HOURS),
+        );
+        ctx.append_http_header(SET_COOKIE, cookie.to_string());
+
+        info!(
+            "Admin {} started impersonating member {}",
+            admin_member.member_id, member.member_id
+        );
+
+        Ok(member)
+    }
+
+    /// End an impersonation and log back in as the admin.
+    #[graphql(name = "stopImpersonation", guard = "AuthGuard")]
+    async fn stop_impersonation(&self, ctx: &Context<'_>) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let config = ctx.data::<Config>().expect("Config must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let (Some(user), Some(impersonator)) = (auth.user(), auth.impersonator()) else {
+            return Err("You aren't impersonating anyone".into());
+        };
+
+        SessionService::end_impersonation(pool.as_ref(), user.member_id, impersonator.member_id)
+            .await?;
+        let token = SessionService::create_session(pool.as_ref(), impersonator.member_id).await?;
+
+        let cookie = session_cookie(config, token, time::Duration::days(SESSION_DURATION_DAYS));
+        ctx.append_http_header(SET_COOKIE, cookie.to_string());
+
+        Ok(true)
+    }
+
+    /// Unlink an account from the logged in member. The last account can't be unlinked,
+    /// since the member couldn't log in anymore.
+    #[graphql(name = "unlinkIdentity", guard = "AuthGuard")]
+    async fn unlink_identity(&self, ctx: &Context<'_>, identity_id: i32) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let user = auth.user.as_ref().ok_or("User not found in context")?;
+
+        let result = sqlx::query(
+            "DELETE FROM MemberIdentity i
+            WHERE i.identity_id = $1 AND i.member_id = $2
+            AND EXISTS (
+                SELECT 1 FROM MemberIdentity o
+                WHERE o.member_id = i.member_id AND o.identity_id <> i.identity_id
+            )",
+        )
+        .bind(identity_id)
+        .bind(user.member_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err("Identity not found, or it is your only one".into());
+        }
+        Ok(true)
+    }
+
+    /// Email a single-use login link to the member with this email. Always returns true,
+    /// whether or not such a member exists.
+    #[graphql(name = "requestLoginLink")]
+    async fn request_login_link(&self, ctx: &Context<'_>, email: String) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let magic_link = ctx
+            .data::<MagicLinkService>()
+            .expect("MagicLinkService must be in context.");
+
+        magic_link.request_link(pool.as_ref(), &email)?;
+
+        Ok(true)
+    }
 }
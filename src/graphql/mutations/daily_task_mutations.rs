@@ -0,0 +1,43 @@
+use crate::auth::guards::AdminGuard;
+use crate::auth::AuthContext;
+use crate::daily_task;
+use crate::models::daily_task::DailyTaskRun;
+use async_graphql::{Context, Object, Result};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct DailyTaskMutations;
+
+#[Object]
+impl DailyTaskMutations {
+    /// Create any missing attendance and status update rows for each date in the range (Admin only)
+    #[graphql(name = "backfillDailyRecords", guard = "AdminGuard")]
+    async fn backfill_daily_records(
+        &self,
+        ctx: &Context<'_>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<DailyTaskRun>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        let runs = daily_task::backfill(
+            pool.as_ref(),
+            start_date,
+            end_date,
+            Some(admin_member.member_id),
+        )
+        .await?;
+
+        Ok(runs)
+    }
+}
@@ -0,0 +1,74 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::github::{org_sync, GitHubClient};
+use crate::models::github::{GitHubOrgSync, GitHubTeamMapping};
+
+#[derive(Default)]
+pub struct GitHubMutations;
+
+#[Object]
+impl GitHubMutations {
+    /// Run the GitHub org sync now instead of waiting for the scheduled job (Admin only)
+    #[graphql(name = "syncGithubOrg", guard = "AdminGuard")]
+    async fn sync_github_org(&self, ctx: &Context<'_>) -> Result<GitHubOrgSync> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let client = ctx
+            .data::<GitHubClient>()
+            .expect("GitHubClient must be in context.");
+
+        if !client.has_token() {
+            return Err("GITHUB_TOKEN is not set".into());
+        }
+
+        let sync = org_sync::sync_org(pool.as_ref(), client).await?;
+
+        Ok(sync)
+    }
+
+    /// Assign members of a GitHub team to a track and/or group on every org sync (Admin only)
+    #[graphql(name = "mapGithubTeam", guard = "AdminGuard")]
+    async fn map_github_team(
+        &self,
+        ctx: &Context<'_>,
+        team_slug: String,
+        track_id: Option<i32>,
+        group_id: Option<i32>,
+    ) -> Result<GitHubTeamMapping> {
+        if track_id.is_none() && group_id.is_none() {
+            return Err("Either trackId or groupId is required".into());
+        }
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let mapping = sqlx::query_as::<_, GitHubTeamMapping>(
+            "INSERT INTO GitHubTeamMapping (team_slug, track_id, group_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (team_slug) DO UPDATE SET
+                track_id = EXCLUDED.track_id,
+                group_id = EXCLUDED.group_id
+            RETURNING *",
+        )
+        .bind(team_slug.trim())
+        .bind(track_id)
+        .bind(group_id)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(mapping)
+    }
+
+    #[graphql(name = "unmapGithubTeam", guard = "AdminGuard")]
+    async fn unmap_github_team(&self, ctx: &Context<'_>, team_slug: String) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query("DELETE FROM GitHubTeamMapping WHERE team_slug = $1")
+            .bind(team_slug.trim())
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
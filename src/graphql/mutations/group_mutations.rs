@@ -0,0 +1,173 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::models::{
+    group::{CreateGroupInput, Group, UpdateGroupInput},
+    track::{CreateTrackInput, Track, UpdateTrackInput},
+};
+
+#[derive(Default)]
+pub struct GroupMutations;
+
+#[Object]
+impl GroupMutations {
+    #[graphql(name = "createGroup", guard = "AdminGuard")]
+    async fn create_group(&self, ctx: &Context<'_>, input: CreateGroupInput) -> Result<Group> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let group = sqlx::query_as::<_, Group>(
+            "INSERT INTO MemberGroup (name, description) VALUES ($1, $2) RETURNING *",
+        )
+        .bind(input.name.trim())
+        .bind(input.description)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(group)
+    }
+
+    #[graphql(name = "updateGroup", guard = "AdminGuard")]
+    async fn update_group(
+        &self,
+        ctx: &Context<'_>,
+        group_id: i32,
+        input: UpdateGroupInput,
+    ) -> Result<Group> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let group = sqlx::query_as::<_, Group>(
+            "UPDATE MemberGroup SET
+                name = COALESCE($2, name),
+                description = COALESCE($3, description)
+            WHERE group_id = $1
+            RETURNING *",
+        )
+        .bind(group_id)
+        .bind(input.name.as_deref().map(str::trim))
+        .bind(input.description)
+        .fetch_optional(pool.as_ref())
+        .await?
+        .ok_or("Group not found")?;
+
+        Ok(group)
+    }
+
+    #[graphql(name = "addGroupMentor", guard = "AdminGuard")]
+    async fn add_group_mentor(
+        &self,
+        ctx: &Context<'_>,
+        group_id: i32,
+        member_id: i32,
+    ) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query(
+            "INSERT INTO GroupMentor (group_id, member_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(group_id)
+        .bind(member_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[graphql(name = "removeGroupMentor", guard = "AdminGuard")]
+    async fn remove_group_mentor(
+        &self,
+        ctx: &Context<'_>,
+        group_id: i32,
+        member_id: i32,
+    ) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query("DELETE FROM GroupMentor WHERE group_id = $1 AND member_id = $2")
+            .bind(group_id)
+            .bind(member_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[graphql(name = "createTrack", guard = "AdminGuard")]
+    async fn create_track(&self, ctx: &Context<'_>, input: CreateTrackInput) -> Result<Track> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let track = sqlx::query_as::<_, Track>(
+            "INSERT INTO Track (name, description) VALUES ($1, $2) RETURNING *",
+        )
+        .bind(input.name.trim())
+        .bind(input.description)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(track)
+    }
+
+    #[graphql(name = "updateTrack", guard = "AdminGuard")]
+    async fn update_track(
+        &self,
+        ctx: &Context<'_>,
+        track_id: i32,
+        input: UpdateTrackInput,
+    ) -> Result<Track> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let track = sqlx::query_as::<_, Track>(
+            "UPDATE Track SET
+                name = COALESCE($2, name),
+                description = COALESCE($3, description)
+            WHERE track_id = $1
+            RETURNING *",
+        )
+        .bind(track_id)
+        .bind(input.name.as_deref().map(str::trim))
+        .bind(input.description)
+        .fetch_optional(pool.as_ref())
+        .await?
+        .ok_or("Track not found")?;
+
+        Ok(track)
+    }
+
+    #[graphql(name = "addTrackMentor", guard = "AdminGuard")]
+    async fn add_track_mentor(
+        &self,
+        ctx: &Context<'_>,
+        track_id: i32,
+        member_id: i32,
+    ) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query(
+            "INSERT INTO TrackMentor (track_id, member_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(track_id)
+        .bind(member_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[graphql(name = "removeTrackMentor", guard = "AdminGuard")]
+    async fn remove_track_mentor(
+        &self,
+        ctx: &Context<'_>,
+        track_id: i32,
+        member_id: i32,
+    ) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query("DELETE FROM TrackMentor WHERE track_id = $1 AND member_id = $2")
+            .bind(track_id)
+            .bind(member_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
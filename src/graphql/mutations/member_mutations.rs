@@ -1,6 +1,14 @@
-use crate::auth::guards::AuthGuard;
+use crate::auth::guards::{AdminGuard, AuthGuard};
 use crate::auth::AuthContext;
-use crate::models::member::{Member, UpdateMemberInput};
+use crate::daily_task::today;
+use crate::events::{Event, EventBus};
+use crate::member_lifecycle::{AcademicCalendar, MemberLifecycleService};
+use crate::models::member::{
+    ChangeMembershipStatusInput, Member, MemberEmailAlias, MembershipStatusChange,
+    UpdateMemberInput,
+};
+use crate::models::notification::NotificationChannel;
+use crate::notifications::NotificationService;
 use async_graphql::{Context, Object, Result};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -20,19 +28,40 @@ impl MemberMutations {
 
         let logged_in_user = auth.user.as_ref().ok_or("User not found in context")?;
 
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
+        let batch = calendar.resolve_batch(input.year, input.batch, today());
+
+        let track_id = match (input.track_id, &input.track) {
+            (None, Some(track)) => Some(
+                sqlx::query_scalar::<_, i32>(
+                    "SELECT track_id FROM Track WHERE LOWER(name) = LOWER($1)",
+                )
+                .bind(track)
+                .fetch_optional(pool.as_ref())
+                .await?
+                .ok_or("Track not found")?,
+            ),
+            _ => input.track_id,
+        };
+
         let member = sqlx::query_as::<_, Member>(
             "UPDATE Member SET
                 roll_no = COALESCE($1, roll_no),
                 name = COALESCE($2, name),
                 email = COALESCE($3, email),
                 sex = COALESCE($4, sex),
-                year = COALESCE($5, year),
+                batch = COALESCE($5, batch),
                 hostel = COALESCE($6, hostel),
                 mac_address = COALESCE($7, mac_address),
                 discord_id = COALESCE($8, discord_id),
                 group_id = COALESCE($9, group_id),
-                track = COALESCE($10, track),
-                github_user = COALESCE($11, github_user)
+                track_id = COALESCE($10, track_id),
+                github_user = CASE
+                    WHEN github_id IS NULL THEN COALESCE($11, github_user)
+                    ELSE github_user
+                END
             WHERE member_id = $12
             RETURNING *",
         )
@@ -40,17 +69,139 @@ impl MemberMutations {
         .bind(&input.name)
         .bind(&input.email)
         .bind(input.sex)
-        .bind(input.year)
+        .bind(batch)
         .bind(&input.hostel)
         .bind(&input.mac_address)
         .bind(&input.discord_id)
         .bind(input.group_id)
-        .bind(&input.track)
+        .bind(track_id)
         .bind(&input.github_user)
         .bind(logged_in_user.member_id)
         .fetch_one(pool.as_ref())
         .await?;
 
+        publish_member_updated(ctx, member.member_id).await;
+        Ok(member)
+    }
+
+    /// Opt in to or out of missed status update notifications through a channel. Returns
+    /// the channels still enabled.
+    #[graphql(name = "setMissedUpdateNotifications", guard = "AuthGuard")]
+    async fn set_missed_update_notifications(
+        &self,
+        ctx: &Context<'_>,
+        channel: NotificationChannel,
+        enabled: bool,
+    ) -> Result<Vec<NotificationChannel>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let member = auth
+            .user()
+            .filter(|_| !auth.is_bot())
+            .ok_or("Only members have notification preferences")?;
+
+        NotificationService::set_channel(pool.as_ref(), member.member_id, channel, enabled).await?;
+
+        Ok(NotificationService::enabled_channels(pool.as_ref(), member.member_id).await?)
+    }
+
+    /// Register a secondary email address for a member, used to match status updates
+    #[graphql(name = "addEmailAlias", guard = "AdminGuard")]
+    async fn add_email_alias(
+        &self,
+        ctx: &Context<'_>,
+        member_id: i32,
+        email: String,
+    ) -> Result<MemberEmailAlias> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let alias = sqlx::query_as::<_, MemberEmailAlias>(
+            "INSERT INTO MemberEmailAlias (member_id, email)
+            VALUES ($1, $2)
+            RETURNING *",
+        )
+        .bind(member_id)
+        .bind(email.trim())
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(alias)
+    }
+
+    #[graphql(name = "removeEmailAlias", guard = "AdminGuard")]
+    async fn remove_email_alias(&self, ctx: &Context<'_>, alias_id: i32) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let result = sqlx::query("DELETE FROM MemberEmailAlias WHERE alias_id = $1")
+            .bind(alias_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a member to another lifecycle state, e.g. on leave or alumni (Admin only)
+    #[graphql(name = "changeMembershipStatus", guard = "AdminGuard")]
+    async fn change_membership_status(
+        &self,
+        ctx: &Context<'_>,
+        input: ChangeMembershipStatusInput,
+    ) -> Result<MembershipStatusChange> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        let today = today();
+        let change = MemberLifecycleService::change_status(
+            pool.as_ref(),
+            input.member_id,
+            input.status,
+            input.effective_date.unwrap_or(today),
+            input.reason,
+            Some(admin_member.member_id),
+            today,
+        )
+        .await?;
+
+        publish_member_updated(ctx, input.member_id).await;
+        Ok(change)
+    }
+
+    /// Merge a duplicate member into another, carrying over their attendance and status
+    /// update history. The duplicate is deleted. (Admin only)
+    #[graphql(name = "mergeMembers", guard = "AdminGuard")]
+    async fn merge_members(
+        &self,
+        ctx: &Context<'_>,
+        source_member_id: i32,
+        target_member_id: i32,
+    ) -> Result<Member> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let member = MemberLifecycleService::merge_members(
+            pool.as_ref(),
+            source_member_id,
+            target_member_id,
+        )
+        .await?;
+
+        publish_member_updated(ctx, member.member_id).await;
         Ok(member)
     }
 }
+
+async fn publish_member_updated(ctx: &Context<'_>, member_id: i32) {
+    let bus = ctx
+        .data::<EventBus>()
+        .expect("EventBus must be in context.");
+    bus.publish(Event::MemberUpdated { member_id }).await;
+}
@@ -1,9 +1,19 @@
 pub mod attendance_mutations;
 pub mod auth_mutations;
+pub mod daily_task_mutations;
+pub mod github_mutations;
+pub mod group_mutations;
 pub mod member_mutations;
+pub mod project_mutations;
 pub mod status_mutations;
+pub mod webhook_mutations;
 
 pub use attendance_mutations::AttendanceMutations;
 pub use auth_mutations::AuthMutations;
+pub use daily_task_mutations::DailyTaskMutations;
+pub use github_mutations::GitHubMutations;
+pub use group_mutations::GroupMutations;
 pub use member_mutations::MemberMutations;
+pub use project_mutations::ProjectMutations;
 pub use status_mutations::StatusMutations;
+pub use webhook_mutations::WebhookMutations;
@@ -0,0 +1,313 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+
+use crate::auth::guards::AuthGuard;
+use crate::auth::AuthContext;
+use crate::models::{
+    project::{CreateProjectInput, Project, ProjectMember, ProjectRole, UpdateProjectInput},
+    status_update::StatusUpdateRecord,
+};
+
+#[derive(Default)]
+pub struct ProjectMutations;
+
+/// Admins can manage every project, other members only the projects they lead.
+async fn ensure_can_manage(pool: &PgPool, auth: &AuthContext, project_id: i32) -> Result<()> {
+    let user = auth.user.as_ref().ok_or("User not found in context")?;
+    if auth.is_admin() {
+        return Ok(());
+    }
+
+    let is_lead: bool = sqlx::query_scalar(
+        "SELECT EXISTS (
+            SELECT 1 FROM ProjectMember
+            WHERE project_id = $1 AND member_id = $2 AND role = 'Lead'
+        )",
+    )
+    .bind(project_id)
+    .bind(user.member_id)
+    .fetch_one(pool)
+    .await?;
+
+    if is_lead {
+        Ok(())
+    } else {
+        Err("Only project leads and admins can manage this project".into())
+    }
+}
+
+/// Fails if `member_id` is the project's only lead, since nobody but admins could manage
+/// the project without one. Locks the leads, so that concurrent changes can't remove the
+/// last two at once.
+async fn ensure_other_lead(
+    tx: &mut Transaction<'_, Postgres>,
+    project_id: i32,
+    member_id: i32,
+) -> Result<()> {
+    let leads: Vec<i32> = sqlx::query_scalar(
+        "SELECT member_id FROM ProjectMember
+        WHERE project_id = $1 AND role = 'Lead'
+        FOR UPDATE",
+    )
+    .bind(project_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    if leads == [member_id] {
+        return Err("A project must keep at least one lead".into());
+    }
+    Ok(())
+}
+
+async fn set_tracks(
+    tx: &mut Transaction<'_, Postgres>,
+    project_id: i32,
+    track_ids: &[i32],
+) -> Result<()> {
+    sqlx::query("DELETE FROM ProjectTrack WHERE project_id = $1")
+        .bind(project_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO ProjectTrack (project_id, track_id)
+        SELECT $1, track_id FROM UNNEST($2::INT[]) AS t(track_id)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(project_id)
+    .bind(track_ids)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[Object]
+impl ProjectMutations {
+    /// Create a project. The creator is added as its lead.
+    #[graphql(name = "createProject", guard = "AuthGuard")]
+    async fn create_project(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateProjectInput,
+    ) -> Result<Project> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let user = auth.user.as_ref().ok_or("User not found in context")?;
+
+        let mut tx = pool.begin().await?;
+
+        let project = sqlx::query_as::<_, Project>(
+            "INSERT INTO Project (title, description, repository_url, status)
+            VALUES ($1, $2, $3, COALESCE($4, 'Active'::project_status))
+            RETURNING *",
+        )
+        .bind(input.title.trim())
+        .bind(input.description)
+        .bind(input.repository_url)
+        .bind(input.status)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(track_ids) = input.track_ids {
+            set_tracks(&mut tx, project.project_id, &track_ids).await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO ProjectMember (project_id, member_id, role) VALUES ($1, $2, 'Lead')",
+        )
+        .bind(project.project_id)
+        .bind(user.member_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(project)
+    }
+
+    #[graphql(name = "updateProject", guard = "AuthGuard")]
+    async fn update_project(
+        &self,
+        ctx: &Context<'_>,
+        project_id: i32,
+        input: UpdateProjectInput,
+    ) -> Result<Project> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        ensure_can_manage(pool, auth, project_id).await?;
+
+        let mut tx = pool.begin().await?;
+
+        let project = sqlx::query_as::<_, Project>(
+            "UPDATE Project SET
+                title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                repository_url = COALESCE($4, repository_url),
+                status = COALESCE($5, status)
+            WHERE project_id = $1
+            RETURNING *",
+        )
+        .bind(project_id)
+        .bind(input.title.as_deref().map(str::trim))
+        .bind(input.description)
+        .bind(input.repository_url)
+        .bind(input.status)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or("Project not found")?;
+
+        if let Some(track_ids) = input.track_ids {
+            set_tracks(&mut tx, project_id, &track_ids).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(project)
+    }
+
+    #[graphql(name = "deleteProject", guard = "AuthGuard")]
+    async fn delete_project(&self, ctx: &Context<'_>, project_id: i32) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        ensure_can_manage(pool, auth, project_id).await?;
+
+        let result = sqlx::query("DELETE FROM Project WHERE project_id = $1")
+            .bind(project_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add a member to a project, or change their role if they are already on it. The only
+    /// lead can't be given another role.
+    #[graphql(name = "addProjectMember", guard = "AuthGuard")]
+    async fn add_project_member(
+        &self,
+        ctx: &Context<'_>,
+        project_id: i32,
+        member_id: i32,
+        #[graphql(default_with = "ProjectRole::Contributor")] role: ProjectRole,
+    ) -> Result<ProjectMember> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        ensure_can_manage(pool, auth, project_id).await?;
+
+        let mut tx = pool.begin().await?;
+        if role != ProjectRole::Lead {
+            ensure_other_lead(&mut tx, project_id, member_id).await?;
+        }
+
+        let project_member = sqlx::query_as::<_, ProjectMember>(
+            "INSERT INTO ProjectMember (project_id, member_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (project_id, member_id) DO UPDATE SET role = EXCLUDED.role
+            RETURNING *",
+        )
+        .bind(project_id)
+        .bind(member_id)
+        .bind(role)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(project_member)
+    }
+
+    /// Remove a member from a project. Members can always remove themselves, unless they
+    /// are its only lead.
+    #[graphql(name = "removeProjectMember", guard = "AuthGuard")]
+    async fn remove_project_member(
+        &self,
+        ctx: &Context<'_>,
+        project_id: i32,
+        member_id: i32,
+    ) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let user = auth.user.as_ref().ok_or("User not found in context")?;
+        if user.member_id != member_id {
+            ensure_can_manage(pool, auth, project_id).await?;
+        }
+
+        let mut tx = pool.begin().await?;
+        ensure_other_lead(&mut tx, project_id, member_id).await?;
+
+        let result =
+            sqlx::query("DELETE FROM ProjectMember WHERE project_id = $1 AND member_id = $2")
+                .bind(project_id)
+                .bind(member_id)
+                .execute(&mut *tx)
+                .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set the projects a status update was about, replacing any earlier links.
+    /// Members can link their own updates, admins and bots can link anyone's.
+    #[graphql(name = "linkStatusUpdateProjects", guard = "AuthGuard")]
+    async fn link_status_update_projects(
+        &self,
+        ctx: &Context<'_>,
+        update_id: i32,
+        project_ids: Vec<i32>,
+    ) -> Result<StatusUpdateRecord> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let user = auth.user.as_ref().ok_or("User not found in context")?;
+
+        let mut tx = pool.begin().await?;
+
+        let update = sqlx::query_as::<_, StatusUpdateRecord>(
+            "SELECT * FROM StatusUpdateHistory WHERE update_id = $1",
+        )
+        .bind(update_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or("Status update not found")?;
+
+        if update.member_id != user.member_id && !auth.is_admin() && !auth.is_bot() {
+            return Err("You can only link your own status updates".into());
+        }
+
+        sqlx::query("DELETE FROM StatusUpdateProject WHERE update_id = $1")
+            .bind(update_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO StatusUpdateProject (update_id, project_id)
+            SELECT $1, project_id FROM UNNEST($2::INT[]) AS p(project_id)
+            ON CONFLICT DO NOTHING",
+        )
+        .bind(update_id)
+        .bind(&project_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(update)
+    }
+}
@@ -3,8 +3,16 @@ use chrono::NaiveDate;
 use sqlx::PgPool;
 use std::sync::Arc;
 
-use crate::auth::guards::{AdminGuard, AdminOrBotGuard};
-use crate::models::status_update::{CreateStatusBreakInput, StatusBreakRecord, StatusUpdateRecord};
+use crate::auth::guards::{AdminOrBotGuard, PermissionGuard};
+use crate::auth::AuthContext;
+use crate::events::{Event, EventBus};
+use crate::member_lifecycle::AcademicCalendar;
+use crate::models::auth::Permission;
+use crate::models::status_update::{
+    CreateStatusBreakInput, MarkStatusUpdateResult, StatusBreakRecord, StatusUpdateRecord,
+};
+use crate::models::webhook::WebhookEvent;
+use crate::webhooks::WebhookService;
 
 #[derive(Default)]
 pub struct StatusMutations;
@@ -17,41 +25,132 @@ impl StatusMutations {
         ctx: &Context<'_>,
         emails: Vec<String>,
         date: NaiveDate,
-    ) -> Result<Vec<StatusUpdateRecord>> {
+    ) -> Result<MarkStatusUpdateResult> {
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context");
-        #[allow(deprecated)]
-        let status = sqlx::query_as::<_, StatusUpdateRecord>(
-            "UPDATE StatusUpdateHistory SET
-                is_sent = true
-            WHERE member_id IN (SELECT member_id from Member where email = ANY($1))
-            AND date = $2
+
+        // Resolve every email against both the primary email and the aliases.
+        // Emails that don't match anyone come back with a NULL member_id.
+        let matches: Vec<(String, Option<i32>)> = sqlx::query_as(
+            "SELECT e.email, known.member_id
+            FROM UNNEST($1::TEXT[]) AS e(email)
+            LEFT JOIN (
+                SELECT member_id, email FROM Member
+                UNION
+                SELECT member_id, email FROM MemberEmailAlias
+            ) known ON LOWER(known.email) = LOWER(e.email)
+            ",
+        )
+        .bind(&emails)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let mut member_ids = Vec::new();
+        let mut unknown_emails = Vec::new();
+        for (email, member_id) in matches {
+            match member_id {
+                Some(id) if !member_ids.contains(&id) => member_ids.push(id),
+                Some(_) => {}
+                None => unknown_emails.push(email),
+            }
+        }
+
+        let mut tx = pool.begin().await?;
+
+        let existing: Vec<i32> = sqlx::query_scalar(
+            "SELECT member_id FROM StatusUpdateHistory
+            WHERE member_id = ANY($1) AND date = $2
+            ",
+        )
+        .bind(&member_ids)
+        .bind(date)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let missing_row_member_ids = member_ids
+            .iter()
+            .copied()
+            .filter(|id| !existing.contains(id))
+            .collect();
+
+        let updated = sqlx::query_as::<_, StatusUpdateRecord>(
+            "INSERT INTO StatusUpdateHistory (member_id, date, is_sent)
+            SELECT member_id, $2, true FROM UNNEST($1::INT[]) AS member_id
+            ON CONFLICT (member_id, date) DO UPDATE SET is_sent = true
             RETURNING *
             ",
         )
-        .bind(emails)
+        .bind(&member_ids)
         .bind(date)
-        .fetch_all(pool.as_ref())
+        .fetch_all(&mut *tx)
         .await?;
 
-        Ok(status)
+        tx.commit().await?;
+
+        let bus = ctx
+            .data::<EventBus>()
+            .expect("EventBus must be in context.");
+        for record in &updated {
+            WebhookService::try_enqueue(
+                pool.as_ref(),
+                WebhookEvent::StatusSent,
+                serde_json::json!({
+                    "updateId": record.update_id,
+                    "memberId": record.member_id,
+                    "date": record.date,
+                }),
+            )
+            .await;
+            bus.publish(Event::StatusUpdateReceived {
+                update_id: record.update_id,
+                member_id: record.member_id,
+                date: record.date,
+            })
+            .await;
+        }
+
+        Ok(MarkStatusUpdateResult {
+            updated,
+            unknown_emails,
+            missing_row_member_ids,
+        })
     }
 
-    #[graphql(name = "createStatusBreak", guard = "AdminGuard")]
+    /// Needs `breaks.manage` over every member, since breaks apply to whole batches
+    #[graphql(
+        name = "createStatusBreak",
+        guard = "PermissionGuard(\"breaks.manage\")"
+    )]
     async fn create_status_break(
         &self,
         ctx: &Context<'_>,
         input: CreateStatusBreakInput,
     ) -> Result<StatusBreakRecord> {
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        if !auth.has_permission(Permission::BreaksManage) {
+            return Err(
+                "Managing breaks needs a role that isn't scoped to a track or group".into(),
+            );
+        }
+
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context");
+
+        let batch = calendar.resolve_batch(input.year, input.batch, input.start_date);
+
         let status = sqlx::query_as::<_, StatusBreakRecord>(
-            "INSERT INTO StatusBreaks (start_date, end_date, year, reason)
+            "INSERT INTO StatusBreaks (start_date, end_date, batch, reason)
              VALUES ($1, $2, $3, $4)
              RETURNING *
             ",
         )
         .bind(input.start_date)
         .bind(input.end_date)
-        .bind(input.year)
+        .bind(batch)
         .bind(&input.reason)
         .fetch_one(pool.as_ref())
         .await?;
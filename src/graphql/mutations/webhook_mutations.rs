@@ -0,0 +1,80 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::auth::AuthContext;
+use crate::models::webhook::{
+    CreateWebhookInput, UpdateWebhookInput, Webhook, WebhookDelivery, WebhookResponse,
+};
+use crate::webhooks::WebhookService;
+
+#[derive(Default)]
+pub struct WebhookMutations;
+
+#[Object]
+impl WebhookMutations {
+    /// Register a URL to be sent events, signed with a new secret (Admin only)
+    #[graphql(name = "createWebhook", guard = "AdminGuard")]
+    async fn create_webhook(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateWebhookInput,
+    ) -> Result<WebhookResponse> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let admin_member = auth
+            .user
+            .as_ref()
+            .ok_or("Admin member not found in context")?;
+
+        let webhook =
+            WebhookService::create(pool.as_ref(), input, Some(admin_member.member_id)).await?;
+
+        Ok(webhook)
+    }
+
+    #[graphql(name = "updateWebhook", guard = "AdminGuard")]
+    async fn update_webhook(
+        &self,
+        ctx: &Context<'_>,
+        webhook_id: i32,
+        input: UpdateWebhookInput,
+    ) -> Result<Webhook> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let webhook = WebhookService::update(pool.as_ref(), webhook_id, input)
+            .await?
+            .ok_or("Webhook not found")?;
+
+        Ok(webhook)
+    }
+
+    /// Delete a webhook and its delivery log (Admin only)
+    #[graphql(name = "deleteWebhook", guard = "AdminGuard")]
+    async fn delete_webhook(&self, ctx: &Context<'_>, webhook_id: i32) -> Result<bool> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        Ok(WebhookService::delete(pool.as_ref(), webhook_id).await?)
+    }
+
+    /// Send a delivery's payload again as a new delivery, e.g. once a failing receiver is
+    /// fixed (Admin only)
+    #[graphql(name = "redeliverWebhook", guard = "AdminGuard")]
+    async fn redeliver_webhook(
+        &self,
+        ctx: &Context<'_>,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let delivery = WebhookService::redeliver(pool.as_ref(), delivery_id)
+            .await?
+            .ok_or("Delivery not found")?;
+
+        Ok(delivery)
+    }
+}
@@ -0,0 +1,33 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::audit::AuditService;
+use crate::auth::guards::AdminGuard;
+use crate::models::audit::{AuditLogEntry, AuditLogFilter};
+
+#[derive(Default)]
+pub struct AuditQueries;
+
+#[Object]
+impl AuditQueries {
+    /// Search the audit log, newest entries first (Admin only)
+    #[graphql(guard = "AdminGuard")]
+    async fn audit_log(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: AuditLogFilter,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        if !(1..=500).contains(&limit) || offset < 0 {
+            return Err("limit must be between 1 and 500, and offset can't be negative".into());
+        }
+
+        let entries = AuditService::search(pool.as_ref(), filter, limit, offset).await?;
+
+        Ok(entries)
+    }
+}
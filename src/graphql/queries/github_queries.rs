@@ -0,0 +1,60 @@
+use async_graphql::{ComplexObject, Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::models::github::{GitHubOrgSync, GitHubOrgSyncDifference, GitHubTeamMapping};
+
+#[derive(Default)]
+pub struct GitHubQueries;
+
+#[Object]
+impl GitHubQueries {
+    /// Latest GitHub org syncs, newest first (Admin only)
+    #[graphql(name = "githubOrgSyncs", guard = "AdminGuard")]
+    async fn github_org_syncs(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: i64,
+    ) -> Result<Vec<GitHubOrgSync>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let syncs = sqlx::query_as::<_, GitHubOrgSync>(
+            "SELECT * FROM GitHubOrgSync ORDER BY started_at DESC, sync_id DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(syncs)
+    }
+
+    #[graphql(name = "githubTeamMappings", guard = "AdminGuard")]
+    async fn github_team_mappings(&self, ctx: &Context<'_>) -> Result<Vec<GitHubTeamMapping>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let mappings = sqlx::query_as::<_, GitHubTeamMapping>(
+            "SELECT * FROM GitHubTeamMapping ORDER BY team_slug",
+        )
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(mappings)
+    }
+}
+
+#[ComplexObject]
+impl GitHubOrgSync {
+    async fn differences(&self, ctx: &Context<'_>) -> Result<Vec<GitHubOrgSyncDifference>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let differences = sqlx::query_as::<_, GitHubOrgSyncDifference>(
+            "SELECT * FROM GitHubOrgSyncDifference WHERE sync_id = $1 ORDER BY kind, difference_id",
+        )
+        .bind(self.sync_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(differences)
+    }
+}
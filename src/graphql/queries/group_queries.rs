@@ -0,0 +1,142 @@
+use async_graphql::{ComplexObject, Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AuthGuard;
+use crate::models::{
+    group::Group,
+    member::{Member, MembershipStatus},
+    track::Track,
+};
+
+#[derive(Default)]
+pub struct GroupQueries;
+
+#[Object]
+impl GroupQueries {
+    #[graphql(guard = "AuthGuard")]
+    async fn groups(&self, ctx: &Context<'_>) -> Result<Vec<Group>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let groups = sqlx::query_as::<_, Group>("SELECT * FROM MemberGroup ORDER BY name")
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(groups)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn group(&self, ctx: &Context<'_>, group_id: i32) -> Result<Option<Group>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let group = sqlx::query_as::<_, Group>("SELECT * FROM MemberGroup WHERE group_id = $1")
+            .bind(group_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        Ok(group)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn tracks(&self, ctx: &Context<'_>) -> Result<Vec<Track>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let tracks = sqlx::query_as::<_, Track>("SELECT * FROM Track ORDER BY name")
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(tracks)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn track(&self, ctx: &Context<'_>, track_id: i32) -> Result<Option<Track>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let track = sqlx::query_as::<_, Track>("SELECT * FROM Track WHERE track_id = $1")
+            .bind(track_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        Ok(track)
+    }
+}
+
+#[ComplexObject]
+impl Group {
+    /// Only current members (active or on leave) are returned unless `statuses` is given.
+    async fn members(
+        &self,
+        ctx: &Context<'_>,
+        statuses: Option<Vec<MembershipStatus>>,
+    ) -> Result<Vec<Member>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let members = sqlx::query_as::<_, Member>(
+            "SELECT * FROM Member
+            WHERE group_id = $1 AND membership_status = ANY($2)
+            ORDER BY name",
+        )
+        .bind(self.group_id)
+        .bind(statuses.unwrap_or_else(|| MembershipStatus::CURRENT.to_vec()))
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(members)
+    }
+
+    async fn mentors(&self, ctx: &Context<'_>) -> Result<Vec<Member>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let mentors = sqlx::query_as::<_, Member>(
+            "SELECT m.* FROM Member m
+            INNER JOIN GroupMentor gm ON gm.member_id = m.member_id
+            WHERE gm.group_id = $1
+            ORDER BY m.name",
+        )
+        .bind(self.group_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(mentors)
+    }
+}
+
+#[ComplexObject]
+impl Track {
+    /// Only current members (active or on leave) are returned unless `statuses` is given.
+    async fn members(
+        &self,
+        ctx: &Context<'_>,
+        statuses: Option<Vec<MembershipStatus>>,
+    ) -> Result<Vec<Member>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let members = sqlx::query_as::<_, Member>(
+            "SELECT * FROM Member
+            WHERE track_id = $1 AND membership_status = ANY($2)
+            ORDER BY name",
+        )
+        .bind(self.track_id)
+        .bind(statuses.unwrap_or_else(|| MembershipStatus::CURRENT.to_vec()))
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(members)
+    }
+
+    async fn mentors(&self, ctx: &Context<'_>) -> Result<Vec<Member>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let mentors = sqlx::query_as::<_, Member>(
+            "SELECT m.* FROM Member m
+            INNER JOIN TrackMentor tm ON tm.member_id = m.member_id
+            WHERE tm.track_id = $1
+            ORDER BY m.name",
+        )
+        .bind(self.track_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(mentors)
+    }
+}
@@ -1,12 +1,28 @@
 use crate::auth::guards::AuthGuard;
+use crate::auth::permissions::PermissionService;
 use crate::auth::AuthContext;
+use crate::daily_task::today;
+use crate::graphql::limits::{
+    check_date_range, date_range_cost, AGGREGATE_COST, HISTORY_SCAN_COST, MEMBER_LIST_COST,
+};
+use crate::member_lifecycle::AcademicCalendar;
 use crate::models::{attendance::AttendanceRecord, status_update::StatusUpdateRecord};
+use crate::notifications::NotificationService;
 use async_graphql::{ComplexObject, Context, Object, Result};
 use chrono::NaiveDate;
 use sqlx::PgPool;
 use std::sync::Arc;
 
-use crate::models::{member::Member, status_update::StatusUpdateStreakRecord};
+use crate::models::{
+    auth::{MemberAccessRole, MemberIdentity, Permission},
+    github::GitHubContribution,
+    group::Group,
+    member::{Member, MemberEmailAlias, MembershipStatus, MembershipStatusChange},
+    notification::NotificationChannel,
+    project::{Project, ProjectStatus},
+    status_update::StatusUpdateStreakRecord,
+    track::Track,
+};
 
 #[derive(Default)]
 pub struct MemberQueries;
@@ -19,26 +35,59 @@ pub struct AttendanceInfo {
     member_id: i32,
 }
 
+pub struct GitHubInfo {
+    member_id: i32,
+    username: String,
+}
+
 #[Object]
 impl MemberQueries {
-    #[graphql(guard = "AuthGuard")]
+    /// Only current members (active or on leave) are returned unless `statuses` is given.
+    /// `year` is the current year of study and is ignored if `batch` is given.
+    #[graphql(
+        guard = "AuthGuard",
+        complexity = "MEMBER_LIST_COST * child_complexity"
+    )]
+    #[allow(clippy::too_many_arguments)]
     pub async fn all_members(
         &self,
         ctx: &Context<'_>,
         year: Option<i32>,
-        track: Option<String>,
+        batch: Option<i32>,
+        track_id: Option<i32>,
+        #[graphql(deprecation = "Use `trackId`.")] track: Option<String>,
+        group_id: Option<i32>,
+        statuses: Option<Vec<MembershipStatus>>,
     ) -> Result<Vec<Member>> {
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
 
         let mut query = sqlx::QueryBuilder::new("SELECT * FROM Member WHERE 1=1");
 
-        if let Some(y) = year {
-            query.push(" AND year = ");
-            query.push_bind(y);
+        query.push(" AND membership_status = ANY(");
+        query.push_bind(statuses.unwrap_or_else(|| MembershipStatus::CURRENT.to_vec()));
+        query.push(")");
+
+        if let Some(b) = calendar.resolve_batch(year, batch, today()) {
+            query.push(" AND batch = ");
+            query.push_bind(b);
+        }
+
+        if let Some(t) = track_id {
+            query.push(" AND track_id = ");
+            query.push_bind(t);
+        }
+
+        if let Some(name) = track {
+            query.push(" AND track_id IN (SELECT track_id FROM Track WHERE LOWER(name) = LOWER(");
+            query.push_bind(name);
+            query.push("))");
         }
 
-        if let Some(g) = track {
-            query.push(" AND track = ");
+        if let Some(g) = group_id {
+            query.push(" AND group_id = ");
             query.push_bind(g);
         }
 
@@ -70,10 +119,17 @@ impl MemberQueries {
                 Ok(member)
             }
             (None, Some(email)) => {
-                let member = sqlx::query_as::<_, Member>("SELECT * FROM Member WHERE email = $1")
-                    .bind(email)
-                    .fetch_optional(pool.as_ref())
-                    .await?;
+                let member = sqlx::query_as::<_, Member>(
+                    "SELECT * FROM Member WHERE LOWER(email) = LOWER($1)
+                    OR member_id = (
+                        SELECT member_id FROM MemberEmailAlias WHERE LOWER(email) = LOWER($1)
+                    )
+                    ORDER BY email = $1 DESC
+                    LIMIT 1",
+                )
+                .bind(email)
+                .fetch_optional(pool.as_ref())
+                .await?;
                 Ok(member)
             }
             (Some(_), Some(_)) => Err("Provide only one of member_id or email".into()),
@@ -89,16 +145,28 @@ impl MemberQueries {
         // The AuthGuard ensures that the user is authenticated, so we can unwrap here.
         Ok(auth.user.clone().unwrap())
     }
+
+    /// The admin impersonating the logged in member, if this is an impersonation session
+    #[graphql(guard = "AuthGuard")]
+    async fn impersonator(&self, ctx: &Context<'_>) -> Result<Option<Member>> {
+        let auth = ctx.data::<AuthContext>()?;
+
+        Ok(auth.impersonator.clone())
+    }
 }
 
 #[Object]
 impl StatusInfo {
+    /// At most `GRAPHQL_MAX_DATE_RANGE_DAYS` days
+    #[graphql(complexity = "date_range_cost(start_date, end_date, child_complexity)")]
     async fn records(
         &self,
         ctx: &Context<'_>,
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<StatusUpdateRecord>> {
+        check_date_range(ctx, start_date, end_date)?;
+
         let pool = ctx.data::<Arc<PgPool>>()?;
         let rows = sqlx::query_as::<_, StatusUpdateRecord>(
             "SELECT * FROM StatusUpdateHistory where date BETWEEN $1 and $2 AND member_id = $3",
@@ -130,6 +198,7 @@ impl StatusInfo {
         Ok(rows)
     }
 
+    #[graphql(complexity = "HISTORY_SCAN_COST")]
     async fn streak(&self, ctx: &Context<'_>) -> Result<StatusUpdateStreakRecord> {
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
 
@@ -176,6 +245,7 @@ impl StatusInfo {
         Ok(result)
     }
 
+    #[graphql(complexity = "HISTORY_SCAN_COST")]
     async fn consecutive_misses(&self, ctx: &Context<'_>) -> Result<Option<i64>> {
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
 
@@ -192,7 +262,7 @@ impl StatusInfo {
                 is_sent = TRUE
                 OR NOT EXISTS (
                     SELECT * FROM StatusBreaks sb
-                    WHERE year = (SELECT year from Member where member_id=$1)
+                    WHERE (sb.batch IS NULL OR sb.batch = (SELECT batch FROM Member WHERE member_id=$1))
                     AND suh.date BETWEEN sb.start_date AND sb.end_date
                 )
               )
@@ -209,12 +279,15 @@ impl StatusInfo {
         Ok(result)
     }
 
+    #[graphql(complexity = "AGGREGATE_COST")]
     async fn update_count(
         &self,
         ctx: &Context<'_>,
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<i64> {
+        check_date_range(ctx, start_date, end_date)?;
+
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
 
         let result : i64 = sqlx::query_scalar("SELECT count(*) AS updatecount FROM statusupdatehistory WHERE is_sent = TRUE and member_id=$1 and date BETWEEN $2 and $3;")
@@ -236,7 +309,7 @@ impl StatusUpdateRecord {
         let is_on_break = sqlx::query_scalar(
             "SELECT EXISTS (
                 SELECT 1 from StatusBreaks
-                WHERE year = (SELECT year FROM Member WHERE member_id = $1)
+                WHERE (batch IS NULL OR batch = (SELECT batch FROM Member WHERE member_id = $1))
                 AND $2 BETWEEN start_date AND end_date
             )",
         )
@@ -247,16 +320,36 @@ impl StatusUpdateRecord {
 
         Ok(is_on_break.unwrap_or(false))
     }
+
+    async fn projects(&self, ctx: &Context<'_>) -> Result<Vec<Project>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let projects = sqlx::query_as::<_, Project>(
+            "SELECT p.* FROM Project p
+            INNER JOIN StatusUpdateProject sup ON sup.project_id = p.project_id
+            WHERE sup.update_id = $1
+            ORDER BY p.title",
+        )
+        .bind(self.update_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(projects)
+    }
 }
 
 #[Object]
 impl AttendanceInfo {
+    /// At most `GRAPHQL_MAX_DATE_RANGE_DAYS` days
+    #[graphql(complexity = "date_range_cost(start_date, end_date, child_complexity)")]
     async fn records(
         &self,
         ctx: &Context<'_>,
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<AttendanceRecord>> {
+        check_date_range(ctx, start_date, end_date)?;
+
         let pool = ctx.data::<Arc<PgPool>>()?;
         let rows = sqlx::query_as::<_, AttendanceRecord>(
             "SELECT * FROM Attendance where date BETWEEN $1 and $2 AND member_id = $3",
@@ -288,15 +381,14 @@ impl AttendanceInfo {
         Ok(rows)
     }
 
+    #[graphql(complexity = "AGGREGATE_COST")]
     async fn present_count(
         &self,
         ctx: &Context<'_>,
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<i64> {
-        if end_date < start_date {
-            return Err("end_date must be >= start_date".into());
-        }
+        check_date_range(ctx, start_date, end_date)?;
 
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
 
@@ -318,15 +410,14 @@ impl AttendanceInfo {
         Ok(records)
     }
 
+    #[graphql(complexity = "AGGREGATE_COST")]
     async fn absent_count(
         &self,
         ctx: &Context<'_>,
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<i64> {
-        if end_date < start_date {
-            return Err("end_date must be >= start_date".into());
-        }
+        check_date_range(ctx, start_date, end_date)?;
 
         let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
 
@@ -364,8 +455,108 @@ impl AttendanceInfo {
     }
 }
 
+#[Object]
+impl GitHubInfo {
+    async fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Daily contributions to the organization's repositories. Days without any are left out.
+    /// At most `GRAPHQL_MAX_DATE_RANGE_DAYS` days.
+    #[graphql(complexity = "date_range_cost(from, to, child_complexity)")]
+    async fn contributions(
+        &self,
+        ctx: &Context<'_>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<GitHubContribution>> {
+        check_date_range(ctx, from, to)?;
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let contributions = sqlx::query_as::<_, GitHubContribution>(
+            "SELECT * FROM GitHubContribution
+            WHERE member_id = $1 AND date BETWEEN $2 AND $3
+            ORDER BY date",
+        )
+        .bind(self.member_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(contributions)
+    }
+}
+
 #[ComplexObject]
 impl Member {
+    /// Current year of study, derived from `batch` and the academic calendar
+    async fn year(&self, ctx: &Context<'_>) -> Option<i32> {
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
+        self.batch.map(|b| calendar.year_of_study(b, today()))
+    }
+
+    async fn group(&self, ctx: &Context<'_>) -> Result<Option<Group>> {
+        let Some(group_id) = self.group_id else {
+            return Ok(None);
+        };
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let group = sqlx::query_as::<_, Group>("SELECT * FROM MemberGroup WHERE group_id = $1")
+            .bind(group_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        Ok(group)
+    }
+
+    async fn track_details(&self, ctx: &Context<'_>) -> Result<Option<Track>> {
+        let Some(track_id) = self.track_id else {
+            return Ok(None);
+        };
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let track = sqlx::query_as::<_, Track>("SELECT * FROM Track WHERE track_id = $1")
+            .bind(track_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        Ok(track)
+    }
+
+    /// Name of the member's track
+    #[graphql(deprecation = "Use `trackDetails { name }`.")]
+    async fn track(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(self.track_details(ctx).await?.map(|track| track.name))
+    }
+
+    /// Projects the member is part of. Archived projects are left out unless `statuses` is given.
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        statuses: Option<Vec<ProjectStatus>>,
+    ) -> Result<Vec<Project>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let statuses = statuses.unwrap_or_else(|| ProjectStatus::LISTED.to_vec());
+
+        let projects = sqlx::query_as::<_, Project>(
+            "SELECT p.* FROM Project p
+            INNER JOIN ProjectMember pm ON pm.project_id = p.project_id
+            WHERE pm.member_id = $1 AND p.status = ANY($2)
+            ORDER BY p.updated_at DESC",
+        )
+        .bind(self.member_id)
+        .bind(statuses)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(projects)
+    }
+
     async fn status(&self, _ctx: &Context<'_>) -> StatusInfo {
         StatusInfo {
             member_id: self.member_id,
@@ -377,4 +568,114 @@ impl Member {
             member_id: self.member_id,
         }
     }
+
+    /// `None` if the member hasn't set a GitHub username
+    async fn github(&self, _ctx: &Context<'_>) -> Option<GitHubInfo> {
+        self.github_user.clone().map(|username| GitHubInfo {
+            member_id: self.member_id,
+            username,
+        })
+    }
+
+    async fn membership_status_history(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<MembershipStatusChange>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let changes = sqlx::query_as::<_, MembershipStatusChange>(
+            "SELECT * FROM MembershipStatusChange
+            WHERE member_id = $1
+            ORDER BY created_at DESC, change_id DESC",
+        )
+        .bind(self.member_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(changes)
+    }
+
+    /// Only visible to the member and users with `members.read_private` over them.
+    async fn hostel(&self, ctx: &Context<'_>) -> Option<&str> {
+        self.can_read_private(ctx)
+            .then_some(self.hostel.as_deref())
+            .flatten()
+    }
+
+    /// Only visible to the member and users with `members.read_private` over them.
+    async fn mac_address(&self, ctx: &Context<'_>) -> Option<&str> {
+        self.can_read_private(ctx)
+            .then_some(self.mac_address.as_deref())
+            .flatten()
+    }
+
+    /// Channels the member gets missed status update notifications through. Only visible
+    /// to the member and users with `members.read_private` over them.
+    async fn missed_update_channels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<Vec<NotificationChannel>>> {
+        if !self.can_read_private(ctx) {
+            return Ok(None);
+        }
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let channels = NotificationService::enabled_channels(pool.as_ref(), self.member_id).await?;
+
+        Ok(Some(channels))
+    }
+
+    /// Access roles assigned to the member, with the track or group they are scoped to.
+    async fn access_roles(&self, ctx: &Context<'_>) -> Result<Vec<MemberAccessRole>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        Ok(PermissionService::access_roles(pool.as_ref(), self.member_id).await?)
+    }
+
+    async fn email_aliases(&self, ctx: &Context<'_>) -> Result<Vec<MemberEmailAlias>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let aliases = sqlx::query_as::<_, MemberEmailAlias>(
+            "SELECT * FROM MemberEmailAlias WHERE member_id = $1 ORDER BY alias_id",
+        )
+        .bind(self.member_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(aliases)
+    }
+
+    /// Accounts the member logs in with. Only visible to the member and admins.
+    async fn identities(&self, ctx: &Context<'_>) -> Result<Vec<MemberIdentity>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        let user = auth.user.as_ref().ok_or("User not found in context")?;
+        if user.member_id != self.member_id && !auth.is_admin() {
+            return Err("You can only view your own identities".into());
+        }
+
+        let identities = sqlx::query_as::<_, MemberIdentity>(
+            "SELECT * FROM MemberIdentity WHERE member_id = $1 ORDER BY identity_id",
+        )
+        .bind(self.member_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(identities)
+    }
+}
+
+impl Member {
+    fn can_read_private(&self, ctx: &Context<'_>) -> bool {
+        let auth = ctx
+            .data::<AuthContext>()
+            .expect("AuthContext must be in context.");
+
+        auth.user()
+            .is_some_and(|user| user.member_id == self.member_id)
+            || auth.has_permission_over(Permission::MembersReadPrivate, self)
+    }
 }
@@ -1,3 +1,17 @@
+pub mod audit_queries;
+pub mod github_queries;
+pub mod group_queries;
 pub mod member_queries;
+pub mod project_queries;
+pub mod scheduler_queries;
+pub mod status_queries;
+pub mod webhook_queries;
 
+pub use audit_queries::AuditQueries;
+pub use github_queries::GitHubQueries;
+pub use group_queries::GroupQueries;
 pub use member_queries::MemberQueries;
+pub use project_queries::ProjectQueries;
+pub use scheduler_queries::SchedulerQueries;
+pub use status_queries::StatusQueries;
+pub use webhook_queries::WebhookQueries;
@@ -0,0 +1,136 @@
+use async_graphql::{ComplexObject, Context, Object, Result};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AuthGuard;
+use crate::models::{
+    member::Member,
+    project::{Project, ProjectMember, ProjectStatus},
+    status_update::StatusUpdateRecord,
+    track::Track,
+};
+
+#[derive(Default)]
+pub struct ProjectQueries;
+
+#[Object]
+impl ProjectQueries {
+    /// Archived projects are left out unless `statuses` is given.
+    #[graphql(guard = "AuthGuard")]
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        statuses: Option<Vec<ProjectStatus>>,
+        track_id: Option<i32>,
+    ) -> Result<Vec<Project>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let statuses = statuses.unwrap_or_else(|| ProjectStatus::LISTED.to_vec());
+
+        let mut query = sqlx::QueryBuilder::new("SELECT * FROM Project p WHERE p.status = ANY(");
+        query.push_bind(statuses);
+        query.push(")");
+
+        if let Some(t) = track_id {
+            query.push(
+                " AND EXISTS (SELECT 1 FROM ProjectTrack pt WHERE pt.project_id = p.project_id AND pt.track_id = ",
+            );
+            query.push_bind(t);
+            query.push(")");
+        }
+
+        query.push(" ORDER BY p.updated_at DESC, p.project_id DESC");
+
+        let projects = query
+            .build_query_as::<Project>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(projects)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn project(&self, ctx: &Context<'_>, project_id: i32) -> Result<Option<Project>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM Project WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        Ok(project)
+    }
+}
+
+#[ComplexObject]
+impl Project {
+    async fn tracks(&self, ctx: &Context<'_>) -> Result<Vec<Track>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let tracks = sqlx::query_as::<_, Track>(
+            "SELECT t.* FROM Track t
+            INNER JOIN ProjectTrack pt ON pt.track_id = t.track_id
+            WHERE pt.project_id = $1
+            ORDER BY t.name",
+        )
+        .bind(self.project_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(tracks)
+    }
+
+    async fn members(&self, ctx: &Context<'_>) -> Result<Vec<ProjectMember>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let members = sqlx::query_as::<_, ProjectMember>(
+            "SELECT * FROM ProjectMember WHERE project_id = $1 ORDER BY role, joined_at",
+        )
+        .bind(self.project_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(members)
+    }
+
+    /// Sent status updates linked to this project, newest first
+    async fn status_updates(
+        &self,
+        ctx: &Context<'_>,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<StatusUpdateRecord>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let updates = sqlx::query_as::<_, StatusUpdateRecord>(
+            "SELECT suh.* FROM StatusUpdateHistory suh
+            INNER JOIN StatusUpdateProject sup ON sup.update_id = suh.update_id
+            WHERE sup.project_id = $1
+            AND ($2::DATE IS NULL OR suh.date >= $2)
+            AND ($3::DATE IS NULL OR suh.date <= $3)
+            ORDER BY suh.date DESC",
+        )
+        .bind(self.project_id)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(updates)
+    }
+}
+
+#[ComplexObject]
+impl ProjectMember {
+    async fn member(&self, ctx: &Context<'_>) -> Result<Member> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let member = sqlx::query_as::<_, Member>("SELECT * FROM Member WHERE member_id = $1")
+            .bind(self.member_id)
+            .fetch_one(pool.as_ref())
+            .await?;
+
+        Ok(member)
+    }
+}
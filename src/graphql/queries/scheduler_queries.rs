@@ -0,0 +1,49 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::models::scheduler::{ScheduledJob, ScheduledJobRun};
+
+#[derive(Default)]
+pub struct SchedulerQueries;
+
+#[Object]
+impl SchedulerQueries {
+    /// State of every job known to the scheduler (Admin only)
+    #[graphql(guard = "AdminGuard")]
+    async fn scheduled_jobs(&self, ctx: &Context<'_>) -> Result<Vec<ScheduledJob>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let jobs =
+            sqlx::query_as::<_, ScheduledJob>("SELECT * FROM ScheduledJob ORDER BY job_name")
+                .fetch_all(pool.as_ref())
+                .await?;
+
+        Ok(jobs)
+    }
+
+    /// Most recent run attempts, optionally for a single job (Admin only)
+    #[graphql(guard = "AdminGuard")]
+    async fn scheduled_job_runs(
+        &self,
+        ctx: &Context<'_>,
+        job_name: Option<String>,
+        #[graphql(default = 20)] limit: i64,
+    ) -> Result<Vec<ScheduledJobRun>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        let runs = sqlx::query_as::<_, ScheduledJobRun>(
+            "SELECT * FROM ScheduledJobRun
+            WHERE $1::TEXT IS NULL OR job_name = $1
+            ORDER BY started_at DESC, run_id DESC
+            LIMIT $2",
+        )
+        .bind(job_name)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(runs)
+    }
+}
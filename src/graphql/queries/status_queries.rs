@@ -0,0 +1,356 @@
+use async_graphql::{Context, Object, Result};
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use std::sync::Arc;
+
+use crate::auth::guards::{AdminGuard, AuthGuard};
+use crate::daily_task::today;
+use crate::graphql::limits::check_date_range;
+use crate::member_lifecycle::AcademicCalendar;
+use crate::models::member::ACTIVE_MEMBER_FILTER;
+use crate::models::notification::MissedUpdateNotification;
+use crate::models::status_update::{
+    DailySubmissionStats, GroupedSubmissionStats, LeaderboardEntry, LeaderboardMetric,
+    StatsGrouping,
+};
+use crate::notifications::NotificationService;
+
+#[derive(Default)]
+pub struct StatusQueries;
+
+/// Starts a query with a `members` CTE holding the active members matching the given filters.
+/// Callers append further CTEs and the final SELECT.
+/// `track` is the deprecated filter by track name.
+fn members_cte<'a>(
+    batch: Option<i32>,
+    track_id: Option<i32>,
+    track: Option<String>,
+    group_id: Option<i32>,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query = QueryBuilder::new(format!(
+        "WITH members AS (SELECT * FROM Member m WHERE {}",
+        ACTIVE_MEMBER_FILTER
+    ));
+
+    if let Some(b) = batch {
+        query.push(" AND batch = ");
+        query.push_bind(b);
+    }
+
+    if let Some(t) = track_id {
+        query.push(" AND track_id = ");
+        query.push_bind(t);
+    }
+
+    if let Some(name) = track {
+        query.push(" AND track_id IN (SELECT track_id FROM Track WHERE LOWER(name) = LOWER(");
+        query.push_bind(name);
+        query.push("))");
+    }
+
+    if let Some(g) = group_id {
+        query.push(" AND group_id = ");
+        query.push_bind(g);
+    }
+
+    query.push(")");
+    query
+}
+
+#[Object]
+impl StatusQueries {
+    /// Rank members by a status update metric. `startDate` and `endDate` are required for `UPDATE_COUNT`.
+    /// `year` is the current year of study and is ignored if `batch` is given.
+    #[graphql(guard = "AuthGuard")]
+    #[allow(clippy::too_many_arguments)]
+    async fn status_leaderboard(
+        &self,
+        ctx: &Context<'_>,
+        metric: LeaderboardMetric,
+        year: Option<i32>,
+        batch: Option<i32>,
+        track_id: Option<i32>,
+        #[graphql(deprecation = "Use `trackId`.")] track: Option<String>,
+        group_id: Option<i32>,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+        #[graphql(default = 10)] limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        if !(1..=500).contains(&limit) {
+            return Err("limit must be between 1 and 500".into());
+        }
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
+
+        let batch = calendar.resolve_batch(year, batch, today());
+        let mut query = members_cte(batch, track_id, track, group_id);
+
+        // Each metric produces a `scores` CTE of (member_id, value).
+        // The streak CTEs use the same 'islands and gaps' approach as `StatusInfo::streak`,
+        // partitioned by member.
+        match metric {
+            LeaderboardMetric::CurrentStreak | LeaderboardMetric::MaxStreak => {
+                query.push(
+                    ",
+                    numbered AS (
+                        SELECT
+                            s.member_id,
+                            s.date,
+                            ROW_NUMBER() OVER (PARTITION BY s.member_id ORDER BY s.date) AS rn
+                        FROM StatusUpdateHistory s
+                        INNER JOIN members m ON m.member_id = s.member_id
+                        WHERE s.is_sent = TRUE
+                    ),
+                    grouped AS (
+                        SELECT member_id, COUNT(*) AS streak, MAX(date) AS end_date
+                        FROM numbered
+                        GROUP BY member_id, date - rn * INTERVAL '1 day'
+                    ),
+                    scores AS (
+                        SELECT member_id, ",
+                );
+                if metric == LeaderboardMetric::CurrentStreak {
+                    query.push("MAX(streak) FILTER (WHERE end_date = CURRENT_DATE - 1)");
+                } else {
+                    query.push("MAX(streak)");
+                }
+                query.push(" AS value FROM grouped GROUP BY member_id)");
+            }
+            LeaderboardMetric::UpdateCount => {
+                let (Some(start_date), Some(end_date)) = (start_date, end_date) else {
+                    return Err("start_date and end_date are required for UPDATE_COUNT".into());
+                };
+                check_date_range(ctx, start_date, end_date)?;
+
+                query.push(
+                    ",
+                    scores AS (
+                        SELECT member_id, COUNT(*) AS value
+                        FROM StatusUpdateHistory
+                        WHERE is_sent = TRUE AND date BETWEEN ",
+                );
+                query.push_bind(start_date);
+                query.push(" AND ");
+                query.push_bind(end_date);
+                query.push(" GROUP BY member_id)");
+            }
+            LeaderboardMetric::MissStreak => {
+                // Matches `StatusInfo::consecutive_misses`: unsent days after the last sent
+                // update, not counting today's row or days that fall in a break.
+                query.push(
+                    ",
+                    last_sent AS (
+                        SELECT member_id, MAX(date) AS last_date
+                        FROM StatusUpdateHistory
+                        WHERE is_sent = TRUE
+                        GROUP BY member_id
+                    ),
+                    scores AS (
+                        SELECT ls.member_id, GREATEST(COUNT(suh.date) - 1, 0) AS value
+                        FROM last_sent ls
+                        INNER JOIN members m ON m.member_id = ls.member_id
+                        LEFT JOIN StatusUpdateHistory suh
+                            ON suh.member_id = ls.member_id
+                            AND suh.date > ls.last_date
+                            AND NOT EXISTS (
+                                SELECT 1 FROM StatusBreaks sb
+                                WHERE (sb.batch IS NULL OR sb.batch = m.batch)
+                                AND suh.date BETWEEN sb.start_date AND sb.end_date
+                            )
+                        GROUP BY ls.member_id
+                    )",
+                );
+            }
+        }
+
+        // Members who have never sent an update have no miss streak, so they are left out
+        // of that leaderboard instead of being ranked with a made-up value.
+        if metric == LeaderboardMetric::MissStreak {
+            query.push(
+                "
+                SELECT m.*, s.value, RANK() OVER (ORDER BY s.value DESC) AS rank
+                FROM members m
+                INNER JOIN scores s ON s.member_id = m.member_id",
+            );
+        } else {
+            query.push(
+                "
+                SELECT m.*, COALESCE(s.value, 0) AS value,
+                    RANK() OVER (ORDER BY COALESCE(s.value, 0) DESC) AS rank
+                FROM members m
+                LEFT JOIN scores s ON s.member_id = m.member_id",
+            );
+        }
+        query.push(" ORDER BY rank, m.member_id LIMIT ");
+        query.push_bind(limit);
+
+        let entries = query
+            .build_query_as::<LeaderboardEntry>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(entries)
+    }
+
+    /// Per-day status update submission rates across the selected members
+    #[graphql(guard = "AuthGuard")]
+    #[allow(clippy::too_many_arguments)]
+    async fn status_submission_stats(
+        &self,
+        ctx: &Context<'_>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        year: Option<i32>,
+        batch: Option<i32>,
+        track_id: Option<i32>,
+        #[graphql(deprecation = "Use `trackId`.")] track: Option<String>,
+        group_id: Option<i32>,
+    ) -> Result<Vec<DailySubmissionStats>> {
+        check_date_range(ctx, start_date, end_date)?;
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
+
+        let batch = calendar.resolve_batch(year, batch, today());
+        let mut query = members_cte(batch, track_id, track, group_id);
+        query.push(
+            ",
+            daily AS (
+                SELECT
+                    suh.date,
+                    COUNT(*) FILTER (WHERE suh.is_sent) AS sent,
+                    COUNT(*) FILTER (
+                        WHERE suh.is_sent OR NOT EXISTS (
+                            SELECT 1 FROM StatusBreaks sb
+                            WHERE (sb.batch IS NULL OR sb.batch = m.batch)
+                            AND suh.date BETWEEN sb.start_date AND sb.end_date
+                        )
+                    ) AS expected
+                FROM StatusUpdateHistory suh
+                INNER JOIN members m ON m.member_id = suh.member_id
+                WHERE suh.date BETWEEN ",
+        );
+        query.push_bind(start_date);
+        query.push(" AND ");
+        query.push_bind(end_date);
+        query.push(
+            "
+                GROUP BY suh.date
+            )
+            SELECT
+                date,
+                sent,
+                expected,
+                COALESCE(sent::FLOAT8 / NULLIF(expected, 0), 0) AS submission_rate
+            FROM daily
+            ORDER BY date",
+        );
+
+        let stats = query
+            .build_query_as::<DailySubmissionStats>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Status update submission rates over a date range, aggregated per group or per track.
+    /// Every group or track is listed, including those without members.
+    #[graphql(guard = "AuthGuard")]
+    async fn grouped_submission_stats(
+        &self,
+        ctx: &Context<'_>,
+        group_by: StatsGrouping,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        year: Option<i32>,
+        batch: Option<i32>,
+    ) -> Result<Vec<GroupedSubmissionStats>> {
+        check_date_range(ctx, start_date, end_date)?;
+
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+        let calendar = ctx
+            .data::<AcademicCalendar>()
+            .expect("AcademicCalendar must be in context.");
+
+        let (table, key) = match group_by {
+            StatsGrouping::Group => ("MemberGroup", "group_id"),
+            StatsGrouping::Track => ("Track", "track_id"),
+        };
+
+        let batch = calendar.resolve_batch(year, batch, today());
+        let mut query = members_cte(batch, None, None, None);
+        query.push(format!(
+            ",
+            member_counts AS (
+                SELECT {key} AS id, COUNT(*) AS members FROM members GROUP BY {key}
+            ),
+            submissions AS (
+                SELECT
+                    m.{key} AS id,
+                    COUNT(*) FILTER (WHERE suh.is_sent) AS sent,
+                    COUNT(*) FILTER (
+                        WHERE suh.is_sent OR NOT EXISTS (
+                            SELECT 1 FROM StatusBreaks sb
+                            WHERE (sb.batch IS NULL OR sb.batch = m.batch)
+                            AND suh.date BETWEEN sb.start_date AND sb.end_date
+                        )
+                    ) AS expected
+                FROM StatusUpdateHistory suh
+                INNER JOIN members m ON m.member_id = suh.member_id
+                WHERE suh.date BETWEEN "
+        ));
+        query.push_bind(start_date);
+        query.push(" AND ");
+        query.push_bind(end_date);
+        query.push(format!(
+            "
+                GROUP BY m.{key}
+            )
+            SELECT
+                t.{key} AS id,
+                t.name,
+                COALESCE(c.members, 0) AS members,
+                COALESCE(s.sent, 0) AS sent,
+                COALESCE(s.expected, 0) AS expected,
+                COALESCE(s.sent::FLOAT8 / NULLIF(s.expected, 0), 0) AS submission_rate
+            FROM {table} t
+            LEFT JOIN member_counts c ON c.id = t.{key}
+            LEFT JOIN submissions s ON s.id = t.{key}
+            ORDER BY t.name"
+        ));
+
+        let stats = query
+            .build_query_as::<GroupedSubmissionStats>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Missed status update notifications, newest first (Admin only)
+    #[graphql(guard = "AdminGuard")]
+    async fn missed_update_notifications(
+        &self,
+        ctx: &Context<'_>,
+        member_id: Option<i32>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<MissedUpdateNotification>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        if !(1..=500).contains(&limit) || offset < 0 {
+            return Err("limit must be between 1 and 500, and offset can't be negative".into());
+        }
+
+        let notifications =
+            NotificationService::history(pool.as_ref(), member_id, limit, offset).await?;
+
+        Ok(notifications)
+    }
+}
@@ -0,0 +1,42 @@
+use async_graphql::{Context, Object, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::guards::AdminGuard;
+use crate::models::webhook::{Webhook, WebhookDelivery, WebhookDeliveryStatus};
+use crate::webhooks::WebhookService;
+
+#[derive(Default)]
+pub struct WebhookQueries;
+
+#[Object]
+impl WebhookQueries {
+    #[graphql(guard = "AdminGuard")]
+    async fn webhooks(&self, ctx: &Context<'_>) -> Result<Vec<Webhook>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        Ok(WebhookService::list(pool.as_ref()).await?)
+    }
+
+    /// The delivery log, newest first (Admin only)
+    #[graphql(guard = "AdminGuard")]
+    async fn webhook_deliveries(
+        &self,
+        ctx: &Context<'_>,
+        webhook_id: Option<i32>,
+        status: Option<WebhookDeliveryStatus>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let pool = ctx.data::<Arc<PgPool>>().expect("Pool must be in context.");
+
+        if !(1..=500).contains(&limit) || offset < 0 {
+            return Err("limit must be between 1 and 500, and offset can't be negative".into());
+        }
+
+        let deliveries =
+            WebhookService::deliveries(pool.as_ref(), webhook_id, status, limit, offset).await?;
+
+        Ok(deliveries)
+    }
+}
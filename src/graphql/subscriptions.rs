@@ -0,0 +1,98 @@
+use async_graphql::{Context, Result};
+use chrono::NaiveDate;
+use futures_util::{Stream, StreamExt};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+
+use crate::auth::guards::AuthGuard;
+use crate::events::{Event, EventBus};
+use crate::models::attendance::AttendanceRecord;
+use crate::models::member::Member;
+use crate::models::status_update::StatusUpdateRecord;
+
+/// Served over WebSockets at `/ws`
+#[derive(Default)]
+pub struct Subscription;
+
+#[async_graphql::Subscription]
+impl Subscription {
+    /// Attendance as it's marked or corrected, only on `date` if given
+    #[graphql(name = "attendanceMarked", guard = "AuthGuard")]
+    async fn attendance_marked(
+        &self,
+        ctx: &Context<'_>,
+        date: Option<NaiveDate>,
+    ) -> impl Stream<Item = Result<AttendanceRecord>> {
+        rows(
+            ctx,
+            "SELECT * FROM Attendance WHERE attendance_id = $1",
+            move |event| match event {
+                Event::AttendanceMarked {
+                    attendance_id,
+                    date: marked_on,
+                    ..
+                } if date.is_none_or(|date| date == marked_on) => Some(attendance_id),
+                _ => None,
+            },
+        )
+    }
+
+    /// Status updates as the mail bot reports them
+    #[graphql(name = "statusUpdateReceived", guard = "AuthGuard")]
+    async fn status_update_received(
+        &self,
+        ctx: &Context<'_>,
+    ) -> impl Stream<Item = Result<StatusUpdateRecord>> {
+        rows(
+            ctx,
+            "SELECT * FROM StatusUpdateHistory WHERE update_id = $1",
+            |event| match event {
+                Event::StatusUpdateReceived { update_id, .. } => Some(update_id),
+                _ => None,
+            },
+        )
+    }
+
+    /// Members whose details or membership status changed
+    #[graphql(name = "memberUpdated", guard = "AuthGuard")]
+    async fn member_updated(&self, ctx: &Context<'_>) -> impl Stream<Item = Result<Member>> {
+        rows(
+            ctx,
+            "SELECT * FROM Member WHERE member_id = $1",
+            |event| match event {
+                Event::MemberUpdated { member_id } => Some(member_id),
+                _ => None,
+            },
+        )
+    }
+}
+
+/// Load the row `select` finds for each event that `id` picks out. Rows deleted in the
+/// meantime are skipped.
+fn rows<T, F>(ctx: &Context<'_>, select: &'static str, id: F) -> impl Stream<Item = Result<T>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+    F: Fn(Event) -> Option<i32> + Send + 'static,
+{
+    let pool = ctx
+        .data::<Arc<PgPool>>()
+        .expect("Pool must be in context.")
+        .clone();
+    let bus = ctx
+        .data::<EventBus>()
+        .expect("EventBus must be in context.");
+
+    bus.subscribe().filter_map(move |event| {
+        let pool = pool.clone();
+        let id = id(event);
+        async move {
+            sqlx::query_as::<_, T>(select)
+                .bind(id?)
+                .fetch_optional(pool.as_ref())
+                .await
+                .map_err(async_graphql::Error::from)
+                .transpose()
+        }
+    })
+}
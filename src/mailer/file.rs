@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+use crate::mailer::{Email, Mailer};
+
+/// Appends emails to a file, or prints them to stdout, for local development without a
+/// mail server.
+pub struct FileMailer {
+    path: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = format!(
+            "To: {}\nSubject: {}\n\n{}\n\n---\n",
+            email.to, email.subject, email.body
+        );
+
+        let Some(path) = &self.path else {
+            println!("{}", message);
+            return Ok(());
+        };
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        file.write_all(message.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write email: {}", e))
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use std::env;
+use std::sync::Arc;
+use tracing::info;
+
+pub mod file;
+pub mod smtp;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+/// A plain text email
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), String>;
+}
+
+/// The mailer selected by `MAILER`: `smtp`, `file` or `stdout` (the default).
+pub fn mailer_from_env() -> Result<Arc<dyn Mailer>, String> {
+    let kind = env::var("MAILER").unwrap_or_else(|_| "stdout".to_string());
+    info!("Using {} mailer", kind);
+
+    match kind.as_str() {
+        "smtp" => Ok(Arc::new(SmtpMailer::from_env()?)),
+        "file" => {
+            let path = env::var("MAIL_FILE").map_err(|_| "MAIL_FILE not set".to_string())?;
+            Ok(Arc::new(FileMailer::new(Some(path.into()))))
+        }
+        "stdout" => Ok(Arc::new(FileMailer::new(None))),
+        other => Err(format!("Unknown mailer {}", other)),
+    }
+}
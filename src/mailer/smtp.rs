@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::env;
+
+use crate::mailer::{Email, Mailer};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Connects to `SMTP_HOST` with implicit TLS, STARTTLS or no encryption, as set by
+    /// `SMTP_TLS` (`tls`, `starttls` or `none`, defaults to `starttls`).
+    pub fn from_env() -> Result<Self, String> {
+        let host = env::var("SMTP_HOST").map_err(|_| "SMTP_HOST not set".to_string())?;
+        let from = env::var("MAIL_FROM")
+            .map_err(|_| "MAIL_FROM not set".to_string())?
+            .parse()
+            .map_err(|e| format!("Invalid MAIL_FROM: {}", e))?;
+
+        let mut builder = match env::var("SMTP_TLS").as_deref().unwrap_or("starttls") {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host),
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &host,
+            )),
+            other => return Err(format!("Invalid SMTP_TLS {}", other)),
+        }
+        .map_err(|e| format!("Invalid SMTP_HOST: {}", e))?;
+
+        if let Ok(port) = env::var("SMTP_PORT") {
+            builder = builder.port(port.parse().map_err(|_| "Invalid SMTP_PORT".to_string())?);
+        }
+        if let (Ok(username), Ok(password)) = (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD"))
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email
+                .to
+                .parse()
+                .map_err(|e| format!("Invalid recipient {}: {}", email.to, e))?)
+            .subject(email.subject)
+            .body(email.body)
+            .map_err(|e| format!("Failed to build email: {}", e))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| format!("Failed to send email: {}", e))?;
+        Ok(())
+    }
+}
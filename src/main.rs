@@ -1,25 +1,49 @@
-use async_graphql::EmptySubscription;
+use async_graphql::extensions::Analyzer;
 use axum::http::header::CONTENT_TYPE;
 use axum::http::{HeaderValue, Method};
 use sqlx::Executor;
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use time::UtcOffset;
 use tower_http::cors::CorsLayer;
 use tracing::info;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
-use daily_task::run_daily_task_at_midnight;
+use audit::AuditLog;
+use auth::identity::IdentityProviders;
+use auth::magic_link::MagicLinkService;
+use daily_task::{AttendanceJob, MemberLifecycleJob, SessionCleanupJob, StatusHistoryJob};
 use database_seeder::seed_database;
-use graphql::{Mutation, Query};
+use events::EventBus;
+use github::{GitHubClient, GitHubContributionJob, GitHubOrgSyncJob};
+use graphql::extensions::{PersistedQueries, ReadOnlyImpersonation};
+use graphql::limits::QueryLimits;
+use graphql::{Mutation, Query, Subscription};
+use mailer::mailer_from_env;
+use member_lifecycle::AcademicCalendar;
+use notifications::MissedUpdateJob;
+use rate_limit::{RateLimit, RateLimiter};
 use routes::setup_router;
+use scheduler::Scheduler;
+use webhooks::{StreakBrokenJob, WebhookDispatcher};
 
+pub mod audit;
 pub mod auth;
+pub mod client_ip;
 pub mod daily_task;
 pub mod database_seeder;
+pub mod events;
+pub mod github;
 pub mod graphql;
+pub mod mailer;
+pub mod member_lifecycle;
 pub mod models;
+pub mod notifications;
+pub mod rate_limit;
 pub mod routes;
+pub mod scheduler;
+pub mod webhooks;
 
 /// Handles all over environment variables in one place.
 // TODO: Replace with `Config.rs` crate.
@@ -32,6 +56,10 @@ pub struct Config {
     seeding_enabled: bool,
     pub frontend_url: String,
     pub hostname: String,
+    pub academic_calendar: AcademicCalendar,
+    /// Whether a reverse proxy sets `X-Forwarded-For` to the client's address
+    pub trust_forwarded_for: bool,
+    pub query_limits: QueryLimits,
 }
 
 impl Config {
@@ -47,6 +75,13 @@ impl Config {
                 .unwrap_or(false),
             frontend_url: std::env::var("FRONTEND_URL").expect("FRONTEND_URL not set"),
             hostname: std::env::var("HOSTNAME").expect("HOSTNAME not set"),
+            academic_calendar: std::env::var("ACADEMIC_YEAR_START")
+                .map(|v| AcademicCalendar::parse(&v).expect("ACADEMIC_YEAR_START must be MM-DD"))
+                .unwrap_or_default(),
+            trust_forwarded_for: std::env::var("TRUST_FORWARDED_FOR")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(false),
+            query_limits: QueryLimits::from_env().expect("GraphQL limits must be valid"),
         }
     }
 }
@@ -56,27 +91,63 @@ async fn main() {
     let config = Config::from_env();
     setup_tracing(&config.env);
 
-    let pool = setup_database(&config.database_url).await;
-    let schema = build_graphql_schema(pool.clone(), config.secret_key.clone());
+    let pool = setup_database(&config.database_url, config.academic_calendar).await;
+    let github = GitHubClient::from_env();
+    let mailer = mailer_from_env().expect("Mailer must be configured properly.");
+    let missed_update_job = MissedUpdateJob::from_env(mailer.clone())
+        .expect("Missed update notifications must be configured properly.");
+    let rate_limiter =
+        RateLimiter::from_env(pool.clone()).expect("Rate limits must be configured properly.");
+    let persisted_queries = PersistedQueries::from_env(pool.clone(), &config.env)
+        .expect("Operation allowlist must be configured properly.");
+    let event_bus =
+        EventBus::from_env(pool.clone(), &config.database_url).expect("EVENT_BUS must be valid.");
+    let schema = build_graphql_schema(
+        pool.clone(),
+        &config,
+        github.clone(),
+        MagicLinkService::from_env(mailer),
+        rate_limiter.clone(),
+        persisted_queries,
+        event_bus,
+    );
 
     if config.seeding_enabled {
         info!("Seeding database...");
         seed_database(&pool).await;
     }
 
-    let pool_for_task = pool.clone();
-    tokio::task::spawn(async move {
-        run_daily_task_at_midnight(pool_for_task).await;
-    });
+    Scheduler::new(pool.clone())
+        .register(SessionCleanupJob)
+        .register(MemberLifecycleJob {
+            calendar: config.academic_calendar,
+        })
+        .register(AttendanceJob)
+        .register(StatusHistoryJob)
+        .register(GitHubContributionJob {
+            client: github.clone(),
+        })
+        .register(GitHubOrgSyncJob { client: github })
+        .register(StreakBrokenJob)
+        .register(missed_update_job)
+        .start()
+        .await;
+    WebhookDispatcher::new(pool.clone()).start();
 
     let cors = setup_cors();
-    let router = setup_router(schema, cors, config.clone(), pool);
+    let providers = IdentityProviders::from_env().await;
+    let router = setup_router(schema, cors, config.clone(), pool, providers, rate_limiter);
 
     info!("Starting Root...");
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", config.port))
         .await
         .unwrap();
-    axum::serve(listener, router).await.unwrap();
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 fn setup_tracing(env: &str) {
@@ -119,7 +190,7 @@ fn setup_tracing(env: &str) {
     }
 }
 
-async fn setup_database(database_url: &str) -> Arc<PgPool> {
+async fn setup_database(database_url: &str, calendar: AcademicCalendar) -> Arc<PgPool> {
     let pool = sqlx::postgres::PgPoolOptions::new()
         .min_connections(2)
         .max_connections(3)
@@ -133,21 +204,51 @@ async fn setup_database(database_url: &str) -> Arc<PgPool> {
         .await
         .expect("Pool must be initialized properly.");
 
+    // Migrations that convert between years of study and batches read the start of the
+    // academic year from this setting
+    let mut conn = pool
+        .acquire()
+        .await
+        .expect("Pool must be initialized properly.");
+    sqlx::query("SELECT set_config('root.academic_year_start', $1, false)")
+        .bind(calendar.to_string())
+        .execute(&mut *conn)
+        .await
+        .expect("Failed to set the academic year start.");
     sqlx::migrate!()
-        .run(&pool)
+        .run(&mut *conn)
         .await
         .expect("Failed to run migrations.");
+    drop(conn);
 
     Arc::new(pool)
 }
 
 fn build_graphql_schema(
     pool: Arc<PgPool>,
-    secret_key: String,
-) -> async_graphql::Schema<Query, Mutation, EmptySubscription> {
-    async_graphql::Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+    config: &Config,
+    github: GitHubClient,
+    magic_link: MagicLinkService,
+    rate_limiter: RateLimiter,
+    persisted_queries: PersistedQueries,
+    event_bus: EventBus,
+) -> async_graphql::Schema<Query, Mutation, Subscription> {
+    async_graphql::Schema::build(Query::default(), Mutation::default(), Subscription)
         .data(pool)
-        .data(secret_key)
+        .data(config.secret_key.clone())
+        .data(config.academic_calendar)
+        .data(config.query_limits)
+        .data(config.clone())
+        .data(github)
+        .data(magic_link)
+        .data(event_bus)
+        .extension(persisted_queries)
+        .extension(RateLimit(rate_limiter))
+        .extension(AuditLog)
+        .extension(ReadOnlyImpersonation)
+        .extension(Analyzer)
+        .limit_depth(config.query_limits.max_depth)
+        .limit_complexity(config.query_limits.max_complexity)
         .finish()
 }
 
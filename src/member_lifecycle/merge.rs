@@ -0,0 +1,252 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::info;
+
+use crate::member_lifecycle::MemberLifecycleService;
+use crate::models::member::Member;
+
+/// Columns that point at a member without being part of a key, moved as they are.
+const MEMBER_REFERENCES: [(&str, &str); 12] = [
+    ("Sessions", "member_id"),
+    ("Sessions", "impersonator_id"),
+    ("MemberEmailAlias", "member_id"),
+    ("MembershipStatusChange", "member_id"),
+    ("MembershipStatusChange", "changed_by"),
+    ("GitHubOrgSyncDifference", "member_id"),
+    ("ApiKeys", "created_by"),
+    ("DailyTaskRun", "triggered_by"),
+    ("MemberInvite", "created_by"),
+    ("MemberAccessRole", "assigned_by"),
+    ("Webhook", "created_by"),
+    ("MissedUpdateNotification", "member_id"),
+];
+
+/// Tables keyed by member and something else. Rows the target already has are kept,
+/// the source's copies are dropped with it.
+const MEMBER_KEYS: [(&str, &str); 6] = [
+    ("MemberIdentity", "provider"),
+    ("ProjectMember", "project_id"),
+    ("GroupMentor", "group_id"),
+    ("TrackMentor", "track_id"),
+    ("GitHubContribution", "date"),
+    ("MissedUpdateOptOut", "channel"),
+];
+
+async fn execute(
+    tx: &mut Transaction<'_, Postgres>,
+    sql: &str,
+    source_id: i32,
+    target_id: i32,
+) -> Result<(), String> {
+    sqlx::query(sql)
+        .bind(source_id)
+        .bind(target_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to merge members: {}", e))?;
+    Ok(())
+}
+
+impl MemberLifecycleService {
+    /// Merge a duplicate member into `target_id` and delete it. Attendance and status
+    /// updates are carried over, and on days both have a row the target counts as present
+    /// or sent if either was. Fields the target is missing are taken from the source, and
+    /// the source's email is kept as an alias.
+    pub async fn merge_members(
+        pool: &PgPool,
+        source_id: i32,
+        target_id: i32,
+    ) -> Result<Member, String> {
+        if source_id == target_id {
+            return Err("Cannot merge a member into itself".to_string());
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let members = sqlx::query_as::<_, Member>(
+            "SELECT * FROM Member WHERE member_id IN ($1, $2) AND role <> 'Bot'
+            ORDER BY member_id FOR UPDATE",
+        )
+        .bind(source_id)
+        .bind(target_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to fetch members: {}", e))?;
+
+        let source = members
+            .iter()
+            .find(|m| m.member_id == source_id)
+            .ok_or_else(|| format!("Member {} not found", source_id))?
+            .clone();
+        if !members.iter().any(|m| m.member_id == target_id) {
+            return Err(format!("Member {} not found", target_id));
+        }
+
+        // A pending status change was meant for the duplicate, not the member it merges into
+        sqlx::query(
+            "UPDATE MembershipStatusChange SET cancelled_at = NOW()
+            WHERE member_id = $1 AND applied_at IS NULL AND cancelled_at IS NULL",
+        )
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to cancel pending changes: {}", e))?;
+
+        execute(
+            &mut tx,
+            "UPDATE Attendance t
+            SET is_present = TRUE, time_in = s.time_in, time_out = s.time_out
+            FROM Attendance s
+            WHERE s.member_id = $1 AND t.member_id = $2 AND s.date = t.date
+            AND s.is_present AND NOT t.is_present",
+            source_id,
+            target_id,
+        )
+        .await?;
+
+        execute(
+            &mut tx,
+            "UPDATE StatusUpdateHistory t SET is_sent = TRUE
+            FROM StatusUpdateHistory s
+            WHERE s.member_id = $1 AND t.member_id = $2 AND s.date = t.date
+            AND s.is_sent AND NOT t.is_sent",
+            source_id,
+            target_id,
+        )
+        .await?;
+
+        execute(
+            &mut tx,
+            "INSERT INTO StatusUpdateProject (update_id, project_id)
+            SELECT t.update_id, p.project_id
+            FROM StatusUpdateHistory s
+            JOIN StatusUpdateHistory t ON t.member_id = $2 AND t.date = s.date
+            JOIN StatusUpdateProject p ON p.update_id = s.update_id
+            WHERE s.member_id = $1
+            ON CONFLICT DO NOTHING",
+            source_id,
+            target_id,
+        )
+        .await?;
+
+        for table in ["Attendance", "StatusUpdateHistory"] {
+            execute(
+                &mut tx,
+                &format!(
+                    "UPDATE {table} s SET member_id = $2
+                    WHERE s.member_id = $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM {table} t WHERE t.member_id = $2 AND t.date = s.date
+                    )"
+                ),
+                source_id,
+                target_id,
+            )
+            .await?;
+        }
+
+        for (table, key) in MEMBER_KEYS {
+            execute(
+                &mut tx,
+                &format!(
+                    "UPDATE {table} s SET member_id = $2
+                    WHERE s.member_id = $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM {table} t WHERE t.member_id = $2 AND t.{key} = s.{key}
+                    )"
+                ),
+                source_id,
+                target_id,
+            )
+            .await?;
+        }
+
+        execute(
+            &mut tx,
+            "UPDATE MemberAccessRole s SET member_id = $2
+            WHERE s.member_id = $1
+            AND NOT EXISTS (
+                SELECT 1 FROM MemberAccessRole t
+                WHERE t.member_id = $2 AND t.role = s.role
+                AND t.track_id IS NOT DISTINCT FROM s.track_id
+                AND t.group_id IS NOT DISTINCT FROM s.group_id
+            )",
+            source_id,
+            target_id,
+        )
+        .await?;
+
+        for (table, column) in MEMBER_REFERENCES {
+            execute(
+                &mut tx,
+                &format!("UPDATE {table} SET {column} = $2 WHERE {column} = $1"),
+                source_id,
+                target_id,
+            )
+            .await?;
+        }
+
+        // The source has to go before its unique fields can be copied over
+        sqlx::query("DELETE FROM Member WHERE member_id = $1")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to delete member: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO MemberEmailAlias (member_id, email)
+            SELECT $1, $2 WHERE NOT EXISTS (
+                SELECT 1 FROM Member WHERE member_id = $1 AND LOWER(email) = LOWER($2)
+            )
+            ON CONFLICT DO NOTHING",
+        )
+        .bind(target_id)
+        .bind(&source.email)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to keep email as alias: {}", e))?;
+
+        let member = sqlx::query_as::<_, Member>(
+            "UPDATE Member SET
+                roll_no = COALESCE(roll_no, $2),
+                sex = COALESCE(sex, $3),
+                batch = COALESCE(batch, $4),
+                hostel = COALESCE(hostel, $5),
+                mac_address = COALESCE(mac_address, $6),
+                discord_id = COALESCE(discord_id, $7),
+                group_id = COALESCE(group_id, $8),
+                track_id = COALESCE(track_id, $9),
+                github_user = CASE
+                    WHEN github_id IS NULL AND $11::BIGINT IS NOT NULL THEN $10
+                    ELSE COALESCE(NULLIF(github_user, ''), $10)
+                END,
+                github_id = COALESCE(github_id, $11)
+            WHERE member_id = $1
+            RETURNING *",
+        )
+        .bind(target_id)
+        .bind(source.roll_no)
+        .bind(source.sex)
+        .bind(source.batch)
+        .bind(source.hostel)
+        .bind(source.mac_address)
+        .bind(source.discord_id)
+        .bind(source.group_id)
+        .bind(source.track_id)
+        .bind(source.github_user.filter(|user| !user.is_empty()))
+        .bind(source.github_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update member: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit merge: {}", e))?;
+
+        info!("Merged member {} into {}", source_id, target_id);
+
+        Ok(member)
+    }
+}
@@ -0,0 +1,329 @@
+pub mod merge;
+
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::models::member::{MembershipStatus, MembershipStatusChange};
+
+/// Year of study of members in their final year. They become alumni when the next academic year starts.
+pub const FINAL_YEAR: i32 = 4;
+
+/// The date on which every academic year starts, e.g. July 1st.
+#[derive(Clone, Copy, Debug)]
+pub struct AcademicCalendar {
+    start_month: u32,
+    start_day: u32,
+}
+
+impl AcademicCalendar {
+    /// Parses a `MM-DD` string such as `07-01`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (month, day) = value
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| format!("Expected MM-DD, found '{}'", value))?;
+        let start_month = month
+            .parse()
+            .map_err(|_| format!("Invalid month in '{}'", value))?;
+        let start_day = day
+            .parse()
+            .map_err(|_| format!("Invalid day in '{}'", value))?;
+
+        // 2024 is a leap year, so this accepts every valid month and day.
+        NaiveDate::from_ymd_opt(2024, start_month, start_day)
+            .ok_or_else(|| format!("Invalid date '{}'", value))?;
+
+        Ok(Self {
+            start_month,
+            start_day,
+        })
+    }
+
+    /// First day of the academic year that started in `academic_year`.
+    /// Falls back to the 28th for a Feb 29th start in a non-leap year.
+    pub fn start_of(&self, academic_year: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(academic_year, self.start_month, self.start_day)
+            .or_else(|| NaiveDate::from_ymd_opt(academic_year, self.start_month, 28))
+            .expect("Start month is validated when parsing")
+    }
+
+    /// The calendar year in which the academic year containing `date` started.
+    pub fn academic_year(&self, date: NaiveDate) -> i32 {
+        if date >= self.start_of(date.year()) {
+            date.year()
+        } else {
+            date.year() - 1
+        }
+    }
+
+    /// Year of study on `date` (1 for first years) of members admitted in `batch`.
+    pub fn year_of_study(&self, batch: i32, date: NaiveDate) -> i32 {
+        self.academic_year(date) - batch + 1
+    }
+
+    /// Admission batch of members who are in their `year` of study on `date`.
+    pub fn batch_for_year(&self, year: i32, date: NaiveDate) -> i32 {
+        self.academic_year(date) - year + 1
+    }
+
+    /// Filters accept either a batch or a year of study. `batch` takes precedence.
+    pub fn resolve_batch(
+        &self,
+        year: Option<i32>,
+        batch: Option<i32>,
+        date: NaiveDate,
+    ) -> Option<i32> {
+        batch.or_else(|| year.map(|y| self.batch_for_year(y, date)))
+    }
+}
+
+/// Formats as `MM-DD`, the form `parse` accepts.
+impl std::fmt::Display for AcademicCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}-{:02}", self.start_month, self.start_day)
+    }
+}
+
+impl Default for AcademicCalendar {
+    fn default() -> Self {
+        Self {
+            start_month: 7,
+            start_day: 1,
+        }
+    }
+}
+
+pub struct MemberLifecycleService;
+
+impl MemberLifecycleService {
+    /// Move a member to `to`. Changes effective today or earlier are applied immediately,
+    /// later ones are stored as pending and applied by the lifecycle job. A member has at
+    /// most one pending change, so any earlier pending change is cancelled.
+    pub async fn change_status(
+        pool: &PgPool,
+        member_id: i32,
+        to: MembershipStatus,
+        effective_date: NaiveDate,
+        reason: Option<String>,
+        changed_by: Option<i32>,
+        today: NaiveDate,
+    ) -> Result<MembershipStatusChange, String> {
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let current: MembershipStatus = sqlx::query_scalar(
+            "SELECT membership_status FROM Member WHERE member_id = $1 FOR UPDATE",
+        )
+        .bind(member_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to fetch member: {}", e))?
+        .ok_or_else(|| format!("Member {} not found", member_id))?;
+
+        if !current.can_transition_to(to) {
+            return Err(format!(
+                "Cannot change status from {:?} to {:?}",
+                current, to
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE MembershipStatusChange SET cancelled_at = NOW()
+            WHERE member_id = $1 AND applied_at IS NULL AND cancelled_at IS NULL",
+        )
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to cancel pending changes: {}", e))?;
+
+        let apply_now = effective_date <= today;
+        if apply_now {
+            sqlx::query("UPDATE Member SET membership_status = $2 WHERE member_id = $1")
+                .bind(member_id)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to update member: {}", e))?;
+        }
+
+        let change = sqlx::query_as::<_, MembershipStatusChange>(
+            "INSERT INTO MembershipStatusChange
+                (member_id, from_status, to_status, effective_date, reason, changed_by, applied_at)
+            VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $7 THEN NOW() END)
+            RETURNING *",
+        )
+        .bind(member_id)
+        .bind(current)
+        .bind(to)
+        .bind(effective_date)
+        .bind(reason)
+        .bind(changed_by)
+        .bind(apply_now)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record status change: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit status change: {}", e))?;
+
+        Ok(change)
+    }
+
+    /// Apply pending changes whose effective date has arrived. Changes that are no longer
+    /// valid for the member's current status are cancelled. Returns the number applied.
+    pub async fn apply_pending_changes(pool: &PgPool, today: NaiveDate) -> Result<u64, String> {
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let pending = sqlx::query_as::<_, (i32, i32, MembershipStatus, MembershipStatus)>(
+            "SELECT c.change_id, c.member_id, m.membership_status, c.to_status
+            FROM MembershipStatusChange c
+            INNER JOIN Member m ON m.member_id = c.member_id
+            WHERE c.applied_at IS NULL AND c.cancelled_at IS NULL AND c.effective_date <= $1
+            ORDER BY c.effective_date, c.change_id
+            FOR UPDATE OF c, m",
+        )
+        .bind(today)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to fetch pending changes: {}", e))?;
+
+        let mut applied = 0;
+        for (change_id, member_id, current, to) in pending {
+            if !current.can_transition_to(to) {
+                warn!(
+                    "Cancelling status change {} for member {}: {:?} to {:?} is not allowed",
+                    change_id, member_id, current, to
+                );
+                sqlx::query(
+                    "UPDATE MembershipStatusChange SET cancelled_at = NOW() WHERE change_id = $1",
+                )
+                .bind(change_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to cancel change {}: {}", change_id, e))?;
+                continue;
+            }
+
+            sqlx::query("UPDATE Member SET membership_status = $2 WHERE member_id = $1")
+                .bind(member_id)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to update member {}: {}", member_id, e))?;
+
+            sqlx::query(
+                "UPDATE MembershipStatusChange SET from_status = $2, applied_at = NOW()
+                WHERE change_id = $1",
+            )
+            .bind(change_id)
+            .bind(current)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to apply change {}: {}", change_id, e))?;
+
+            applied += 1;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit pending changes: {}", e))?;
+
+        Ok(applied)
+    }
+
+    /// Once per academic year, move members past their final year to alumni and cancel their
+    /// pending status changes. Everyone else's year of study is derived from their batch, so
+    /// it advances on its own. If no promotion was ever recorded, the current academic year
+    /// is recorded as the baseline without graduating anyone.
+    pub async fn promote_academic_year(
+        pool: &PgPool,
+        calendar: &AcademicCalendar,
+        today: NaiveDate,
+    ) -> Result<(), String> {
+        let academic_year = calendar.academic_year(today);
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        sqlx::query("LOCK TABLE AcademicYearPromotion IN EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to lock promotions: {}", e))?;
+
+        let latest: Option<i32> =
+            sqlx::query_scalar("SELECT MAX(academic_year) FROM AcademicYearPromotion")
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to fetch last promotion: {}", e))?;
+
+        let graduated = match latest {
+            Some(year) if year >= academic_year => return Ok(()),
+            None => {
+                info!("Recording academic year {} as the baseline", academic_year);
+                0
+            }
+            Some(_) => {
+                let graduated = sqlx::query(
+                    "WITH graduating AS (
+                        SELECT member_id, membership_status FROM Member
+                        WHERE batch <= $1 AND membership_status <> 'Alumni' AND role <> 'Bot'
+                        FOR UPDATE
+                    ),
+                    updated AS (
+                        UPDATE Member m SET membership_status = 'Alumni'
+                        FROM graduating g
+                        WHERE m.member_id = g.member_id
+                    ),
+                    -- e.g. a return from leave, which would make them active again
+                    cancelled AS (
+                        UPDATE MembershipStatusChange c SET cancelled_at = NOW()
+                        FROM graduating g
+                        WHERE c.member_id = g.member_id
+                        AND c.applied_at IS NULL AND c.cancelled_at IS NULL
+                    )
+                    INSERT INTO MembershipStatusChange
+                        (member_id, from_status, to_status, effective_date, reason, applied_at)
+                    SELECT member_id, membership_status, 'Alumni', $2, 'Graduated', NOW()
+                    FROM graduating",
+                )
+                .bind(academic_year - FINAL_YEAR)
+                .bind(calendar.start_of(academic_year))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to graduate members: {}", e))?
+                .rows_affected();
+
+                info!(
+                    "Academic year {}: graduated {} members",
+                    academic_year, graduated
+                );
+                graduated
+            }
+        };
+
+        sqlx::query(
+            "INSERT INTO AcademicYearPromotion (academic_year, graduated_members)
+            VALUES ($1, $2)",
+        )
+        .bind(academic_year)
+        .bind(graduated as i32)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record promotion: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit promotion: {}", e))?;
+
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use sqlx::FromRow;
 #[derive(SimpleObject, FromRow)]
 pub struct AttendanceRecord {
     pub attendance_id: i32,
+    pub member_id: i32,
     pub date: NaiveDate,
     pub is_present: bool,
     pub time_in: Option<NaiveTime>,
@@ -15,6 +16,17 @@ pub struct AttendanceRecord {
     pub updated_at: NaiveDateTime,
 }
 
+/// Overwrites a member's attendance on a day. `time_in` and `time_out` are required when
+/// `is_present` is true, and ignored otherwise.
+#[derive(InputObject)]
+pub struct CorrectAttendanceInput {
+    pub member_id: i32,
+    pub date: NaiveDate,
+    pub is_present: bool,
+    pub time_in: Option<NaiveTime>,
+    pub time_out: Option<NaiveTime>,
+}
+
 #[derive(InputObject)]
 pub struct MarkAttendanceInput {
     pub member_id: i32,
@@ -0,0 +1,66 @@
+use crate::models::auth::Role;
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use sqlx::FromRow;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "audit_event")]
+pub enum AuditEvent {
+    Mutation,
+    Login,
+    LoginFailed,
+    ApiKeyUsed,
+    ApiKeyRejected,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct AuditLogEntry {
+    pub audit_id: i64,
+    pub event: AuditEvent,
+    /// Member who acted. Bots have negative IDs, the negated ID of their API key.
+    pub actor_id: Option<i32>,
+    pub actor_role: Option<Role>,
+    /// Admin impersonating the actor, if any
+    pub impersonator_id: Option<i32>,
+    /// Mutation name, or identity provider for logins
+    pub operation: Option<String>,
+    /// Mutation arguments, with secrets redacted
+    pub arguments: Option<Value>,
+    /// ID arguments of the mutation, e.g. `{"memberId": 3}`
+    pub targets: Option<Value>,
+    /// Rows of the targets before the mutation, keyed like `Member:3`
+    pub before: Option<Value>,
+    /// Rows of the targets after the mutation, null if deleted
+    pub after: Option<Value>,
+    pub error: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// An entry to record. Unset fields are stored as null.
+#[derive(Default)]
+pub struct NewAuditEntry {
+    pub actor_id: Option<i32>,
+    pub actor_role: Option<Role>,
+    pub impersonator_id: Option<i32>,
+    pub operation: Option<String>,
+    pub arguments: Option<Value>,
+    pub targets: Option<Value>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub error: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// Filters for searching the audit log. Unset filters match everything.
+#[derive(InputObject, Default)]
+pub struct AuditLogFilter {
+    pub event: Option<AuditEvent>,
+    pub actor_id: Option<i32>,
+    pub operation: Option<String>,
+    /// Matches entries by the member or targeting them
+    pub member_id: Option<i32>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
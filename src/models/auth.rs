@@ -2,6 +2,7 @@ use crate::models::member::Member;
 use async_graphql::{Enum, SimpleObject};
 use chrono::NaiveDateTime;
 use sqlx::FromRow;
+use std::str::FromStr;
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
 #[sqlx(type_name = "role_type")]
@@ -11,6 +12,77 @@ pub enum Role {
     Bot,
 }
 
+/// Roles assigned on top of a member's base `Role`, each granting a fixed set of permissions
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "access_role")]
+pub enum AccessRole {
+    Mentor,
+    TrackLead,
+    AttendanceOfficer,
+}
+
+impl AccessRole {
+    pub fn permissions(self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            Self::Mentor => &[MembersReadPrivate],
+            Self::TrackLead => &[MembersReadPrivate, AttendanceCorrect],
+            Self::AttendanceOfficer => &[AttendanceCorrect, BreaksManage],
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Permission {
+    /// Correct members' attendance records
+    AttendanceCorrect,
+    /// Create status update breaks
+    BreaksManage,
+    /// Read members' private details, e.g. their MAC address
+    MembersReadPrivate,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 3] = [
+        Self::AttendanceCorrect,
+        Self::BreaksManage,
+        Self::MembersReadPrivate,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AttendanceCorrect => "attendance.correct",
+            Self::BreaksManage => "breaks.manage",
+            Self::MembersReadPrivate => "members.read_private",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| format!("Unknown permission {}", s))
+    }
+}
+
+/// A role assigned to a member. Scoped roles only grant their permissions over the members
+/// of `track_id` or `group_id`.
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+pub struct MemberAccessRole {
+    pub assignment_id: i32,
+    pub member_id: i32,
+    pub role: AccessRole,
+    pub track_id: Option<i32>,
+    pub group_id: Option<i32>,
+    pub assigned_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
 #[derive(SimpleObject, FromRow, Debug)]
 pub struct Session {
     pub session_id: i32,
@@ -45,6 +117,39 @@ pub struct ApiKeyResponse {
     pub api_key: String,
 }
 
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct MemberInvite {
+    pub invite_id: i32,
+    pub member_id: i32,
+    #[graphql(skip)]
+    pub code_hash: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+}
+
+/// An account at an identity provider that a member logs in with
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct MemberIdentity {
+    pub identity_id: i32,
+    pub member_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+#[derive(SimpleObject)]
+pub struct MemberInviteResponse {
+    pub invite: MemberInvite,
+    /// The invite code, only returned once. The member logs in with
+    /// `/auth/github?invite=<code>`.
+    pub code: String,
+}
+
 // OAuth callback data (not an input, used internally)
 #[derive(Debug, Clone)]
 pub struct GitHubUser {
@@ -52,4 +157,6 @@ pub struct GitHubUser {
     pub github_username: String,
     pub name: String,
     pub email: String,
+    /// Every verified address on the GitHub account, used to link existing members.
+    pub verified_emails: Vec<String>,
 }
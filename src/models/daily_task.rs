@@ -0,0 +1,20 @@
+use async_graphql::SimpleObject;
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+/// Outcome of generating one kind of daily row (`Attendance` or `StatusUpdateHistory`) for one date.
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct DailyTaskRun {
+    pub run_id: i32,
+    /// Name of the scheduled job that generates these rows.
+    pub job_name: String,
+    pub run_date: NaiveDate,
+    pub succeeded: bool,
+    /// Rows inserted by the run. Members who already had a row for the date aren't counted.
+    pub rows_created: i32,
+    pub error: Option<String>,
+    /// Admin who requested a manual backfill. `None` for scheduled runs.
+    pub triggered_by: Option<i32>,
+    pub started_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
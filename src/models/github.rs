@@ -0,0 +1,53 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+/// A member's public activity on the organization's repositories on a single day
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct GitHubContribution {
+    pub date: NaiveDate,
+    pub commits: i32,
+    pub pull_requests: i32,
+    pub reviews: i32,
+    pub synced_at: NaiveDateTime,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct GitHubTeamMapping {
+    pub team_slug: String,
+    pub track_id: Option<i32>,
+    pub group_id: Option<i32>,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+#[graphql(complex)]
+pub struct GitHubOrgSync {
+    pub sync_id: i32,
+    pub org_members: i32,
+    pub sessions_revoked: i32,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "org_sync_difference_kind")]
+pub enum OrgSyncDifferenceKind {
+    /// A member's GitHub account is no longer in the org. Their sessions were revoked.
+    LeftOrg,
+    /// An org member has no `Member` row.
+    NotRegistered,
+    /// A member's track was updated from a mapped team.
+    TrackChanged,
+    /// A member's group was updated from a mapped team.
+    GroupChanged,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct GitHubOrgSyncDifference {
+    pub difference_id: i32,
+    pub sync_id: i32,
+    pub kind: OrgSyncDifferenceKind,
+    pub member_id: Option<i32>,
+    pub github_user: String,
+    pub detail: Option<String>,
+}
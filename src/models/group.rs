@@ -0,0 +1,24 @@
+use async_graphql::{InputObject, SimpleObject};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+#[graphql(complex)]
+pub struct Group {
+    pub group_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(InputObject)]
+pub struct CreateGroupInput {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateGroupInput {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
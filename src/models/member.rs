@@ -1,6 +1,6 @@
 use crate::models::auth::Role;
 use async_graphql::{Enum, InputObject, SimpleObject};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use sqlx::FromRow;
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
@@ -11,6 +11,38 @@ pub enum Sex {
     Other,
 }
 
+/// Lifecycle state of a member. Only `Active` members get daily attendance and status update rows.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "membership_status")]
+pub enum MembershipStatus {
+    Active,
+    OnLeave,
+    Alumni,
+    Suspended,
+}
+
+impl MembershipStatus {
+    /// Statuses that count as current members, shown in member listings by default.
+    pub const CURRENT: [MembershipStatus; 2] = [Self::Active, Self::OnLeave];
+
+    pub fn can_transition_to(self, to: MembershipStatus) -> bool {
+        use MembershipStatus::*;
+
+        matches!(
+            (self, to),
+            (Active, OnLeave | Alumni | Suspended)
+                | (OnLeave, Active | Alumni | Suspended)
+                | (Suspended, Active | Alumni)
+                | (Alumni, Active)
+        )
+    }
+}
+
+/// SQL condition, on a `Member` aliased as `m`, selecting the members whose attendance
+/// and status updates are tracked. Bots and GitHub-only signups without a `batch` are excluded.
+pub const ACTIVE_MEMBER_FILTER: &str =
+    "m.membership_status = 'Active' AND m.role <> 'Bot' AND m.batch IS NOT NULL";
+
 #[derive(SimpleObject, FromRow, Clone, Debug)]
 #[graphql(complex)]
 pub struct Member {
@@ -19,14 +51,23 @@ pub struct Member {
     pub name: String,
     pub email: String,
     pub sex: Option<Sex>,
-    pub year: Option<i32>,
+    /// Calendar year in which the member's first academic year started, e.g. 2023.
+    pub batch: Option<i32>,
+    /// Private, see the `hostel` resolver.
+    #[graphql(skip)]
     pub hostel: Option<String>,
+    /// Private, see the `macAddress` resolver.
+    #[graphql(skip)]
     pub mac_address: Option<String>,
     pub discord_id: Option<String>,
     pub group_id: Option<i32>,
     pub role: Role,
-    pub track: Option<String>,
+    pub track_id: Option<i32>,
     pub github_user: Option<String>,
+    /// GitHub's numeric user ID, set on the first GitHub login. Unlike `github_user`, it
+    /// doesn't change when the account is renamed.
+    pub github_id: Option<i64>,
+    pub membership_status: MembershipStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -37,12 +78,12 @@ pub struct CreateMemberInput {
     pub name: String,
     pub email: String,
     pub sex: Sex,
-    pub year: i32,
+    pub batch: i32,
     pub hostel: String,
     pub mac_address: String,
     pub discord_id: String,
     pub group_id: i32,
-    pub track: Option<String>,
+    pub track_id: Option<i32>,
     pub github_user: Option<String>,
 }
 
@@ -52,11 +93,52 @@ pub struct UpdateMemberInput {
     pub name: Option<String>,
     pub email: Option<String>,
     pub sex: Option<Sex>,
+    pub batch: Option<i32>,
+    /// Current year of study, converted to a batch. Ignored if `batch` is given.
     pub year: Option<i32>,
     pub hostel: Option<String>,
     pub mac_address: Option<String>,
     pub discord_id: Option<String>,
     pub group_id: Option<i32>,
+    pub track_id: Option<i32>,
+    /// Name of an existing track, ignoring case. Ignored if `track_id` is given, an error if
+    /// no track has this name.
+    #[graphql(deprecation = "Use `trackId`.")]
     pub track: Option<String>,
+    /// Ignored once the member has logged in with GitHub, the login is then kept up to date
+    /// from GitHub.
     pub github_user: Option<String>,
 }
+
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+pub struct MemberEmailAlias {
+    pub alias_id: i32,
+    pub member_id: i32,
+    pub email: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+pub struct MembershipStatusChange {
+    pub change_id: i32,
+    pub member_id: i32,
+    pub from_status: MembershipStatus,
+    pub to_status: MembershipStatus,
+    pub effective_date: NaiveDate,
+    pub reason: Option<String>,
+    /// Admin who requested the change. `None` for automatic changes, e.g. graduation.
+    pub changed_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    /// `None` while the change is pending.
+    pub applied_at: Option<NaiveDateTime>,
+    pub cancelled_at: Option<NaiveDateTime>,
+}
+
+#[derive(InputObject)]
+pub struct ChangeMembershipStatusInput {
+    pub member_id: i32,
+    pub status: MembershipStatus,
+    /// Defaults to today. Changes with a future date are applied on that date.
+    pub effective_date: Option<NaiveDate>,
+    pub reason: Option<String>,
+}
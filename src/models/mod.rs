@@ -1,4 +1,13 @@
 pub mod attendance;
+pub mod audit;
 pub mod auth;
+pub mod daily_task;
+pub mod github;
+pub mod group;
 pub mod member;
+pub mod notification;
+pub mod project;
+pub mod scheduler;
 pub mod status_update;
+pub mod track;
+pub mod webhook;
@@ -0,0 +1,43 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::FromRow;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Hash, sqlx::Type, Debug)]
+#[sqlx(type_name = "notification_channel")]
+pub enum NotificationChannel {
+    /// Sent to the member's email address
+    Email,
+    /// Posted to the club's Discord channel, mentioning the member
+    Discord,
+    /// Sent to webhooks subscribed to `updates.missed`
+    Webhook,
+}
+
+impl NotificationChannel {
+    pub const ALL: [NotificationChannel; 3] = [Self::Email, Self::Discord, Self::Webhook];
+
+    /// Name used in `MISSED_UPDATE_CHANNELS`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Discord => "discord",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct MissedUpdateNotification {
+    pub notification_id: i32,
+    pub member_id: i32,
+    pub channel: NotificationChannel,
+    /// The threshold of consecutive misses that was crossed
+    pub threshold: i32,
+    /// Consecutive misses when the notification was sent, not counting breaks
+    pub misses: i32,
+    /// Date of the member's last update, if they ever sent one
+    pub last_sent_date: Option<NaiveDate>,
+    /// Why sending failed, if it did
+    pub error: Option<String>,
+    pub sent_at: NaiveDateTime,
+}
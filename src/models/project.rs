@@ -0,0 +1,66 @@
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "project_status")]
+pub enum ProjectStatus {
+    Proposed,
+    Active,
+    Completed,
+    Archived,
+}
+
+impl ProjectStatus {
+    /// Statuses shown in project listings by default.
+    pub const LISTED: [ProjectStatus; 3] = [Self::Proposed, Self::Active, Self::Completed];
+}
+
+/// A member's role on a project. Leads can edit the project and manage its members.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "project_role")]
+pub enum ProjectRole {
+    Lead,
+    Contributor,
+    Mentor,
+}
+
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+#[graphql(complex)]
+pub struct Project {
+    pub project_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub repository_url: Option<String>,
+    pub status: ProjectStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(SimpleObject, FromRow, Clone, Debug)]
+#[graphql(complex)]
+pub struct ProjectMember {
+    pub project_id: i32,
+    pub member_id: i32,
+    pub role: ProjectRole,
+    pub joined_at: NaiveDateTime,
+}
+
+#[derive(InputObject)]
+pub struct CreateProjectInput {
+    pub title: String,
+    pub description: Option<String>,
+    pub repository_url: Option<String>,
+    pub status: Option<ProjectStatus>,
+    pub track_ids: Option<Vec<i32>>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateProjectInput {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub repository_url: Option<String>,
+    pub status: Option<ProjectStatus>,
+    /// Replaces the project's tracks when given.
+    pub track_ids: Option<Vec<i32>>,
+}
@@ -0,0 +1,41 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "job_status")]
+pub enum JobStatus {
+    Idle,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct ScheduledJob {
+    pub job_name: String,
+    pub schedule: String,
+    pub status: JobStatus,
+    pub last_run_at: Option<NaiveDateTime>,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    /// Instance currently holding the lease on this job, if any.
+    pub locked_by: Option<String>,
+    pub locked_until: Option<NaiveDateTime>,
+    /// Scheduled time of the most recent run claimed by any instance.
+    pub claimed_run_at: Option<NaiveDateTime>,
+    #[graphql(skip)]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct ScheduledJobRun {
+    pub run_id: i32,
+    pub job_name: String,
+    pub attempt: i32,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
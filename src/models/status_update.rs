@@ -1,4 +1,5 @@
-use async_graphql::{InputObject, SimpleObject};
+use crate::models::member::Member;
+use async_graphql::{Enum, InputObject, SimpleObject};
 use chrono::NaiveDate;
 use sqlx::FromRow;
 
@@ -11,6 +12,17 @@ pub struct StatusUpdateRecord {
     pub is_sent: bool,
 }
 
+/// Outcome of a `markStatusUpdate` call, so that callers can tell which
+/// senders were not matched to a member.
+#[derive(SimpleObject)]
+pub struct MarkStatusUpdateResult {
+    pub updated: Vec<StatusUpdateRecord>,
+    /// Emails that did not match any member's primary email or alias.
+    pub unknown_emails: Vec<String>,
+    /// Members who had no `StatusUpdateHistory` row for the date, which was created instead.
+    pub missing_row_member_ids: Vec<i32>,
+}
+
 #[derive(SimpleObject, FromRow)]
 pub struct StatusUpdateStreakRecord {
     pub current_streak: Option<i64>,
@@ -22,7 +34,8 @@ pub struct StatusBreakRecord {
     pub id: i32,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
-    pub year: i32,
+    /// Batch the break applies to. `None` means it applies to every member.
+    pub batch: Option<i32>,
     pub reason: Option<String>,
 }
 
@@ -30,6 +43,55 @@ pub struct StatusBreakRecord {
 pub struct CreateStatusBreakInput {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
-    pub year: i32,
+    /// Omit both `batch` and `year` to create a break for every member.
+    pub batch: Option<i32>,
+    /// Year of study as of `start_date`, converted to a batch. Ignored if `batch` is given.
+    pub year: Option<i32>,
     pub reason: Option<String>,
 }
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LeaderboardMetric {
+    CurrentStreak,
+    MaxStreak,
+    /// Number of updates sent within the requested date range.
+    UpdateCount,
+    /// Consecutive missed updates, ignoring days that fall in a break.
+    MissStreak,
+}
+
+#[derive(SimpleObject, FromRow)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub value: i64,
+    #[sqlx(flatten)]
+    pub member: Member,
+}
+
+#[derive(SimpleObject, FromRow)]
+pub struct DailySubmissionStats {
+    pub date: NaiveDate,
+    pub sent: i64,
+    /// Members expected to send an update that day. Members on break who didn't send one are excluded.
+    pub expected: i64,
+    pub submission_rate: f64,
+}
+
+/// What `groupedSubmissionStats` aggregates members by.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StatsGrouping {
+    Group,
+    Track,
+}
+
+#[derive(SimpleObject, FromRow)]
+pub struct GroupedSubmissionStats {
+    /// `groupId` or `trackId`, depending on the grouping
+    pub id: i32,
+    pub name: String,
+    pub members: i64,
+    pub sent: i64,
+    /// Updates expected over the whole range. Days on break without an update are excluded.
+    pub expected: i64,
+    pub submission_rate: f64,
+}
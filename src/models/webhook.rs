@@ -0,0 +1,97 @@
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use sqlx::FromRow;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "webhook_event")]
+pub enum WebhookEvent {
+    MemberCreated,
+    AttendanceMarked,
+    StatusSent,
+    StreakBroken,
+    UpdatesMissed,
+}
+
+impl WebhookEvent {
+    /// Name sent to receivers in the payload and `X-Root-Event` header
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MemberCreated => "member.created",
+            Self::AttendanceMarked => "attendance.marked",
+            Self::StatusSent => "status.sent",
+            Self::StreakBroken => "streak.broken",
+            Self::UpdatesMissed => "updates.missed",
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, sqlx::Type, Debug)]
+#[sqlx(type_name = "webhook_delivery_status")]
+pub enum WebhookDeliveryStatus {
+    /// Not delivered yet, or waiting for a retry
+    Pending,
+    Delivered,
+    /// Every attempt failed. Can still be redelivered manually.
+    Failed,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct Webhook {
+    pub webhook_id: i32,
+    pub url: String,
+    #[graphql(skip)]
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A newly created webhook. The secret used to sign its payloads isn't shown again.
+#[derive(SimpleObject)]
+pub struct WebhookResponse {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(SimpleObject, FromRow, Debug)]
+pub struct WebhookDelivery {
+    pub delivery_id: i64,
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    /// The `data` sent to the receiver
+    pub payload: Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    /// When the delivery is next attempted, if it's pending
+    pub next_attempt_at: Option<NaiveDateTime>,
+    /// HTTP status of the last attempt, if the receiver answered
+    pub response_status: Option<i32>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    /// Delivery this one was manually redelivered from
+    pub redelivery_of: Option<i64>,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(InputObject)]
+pub struct CreateWebhookInput {
+    /// `http` or `https` URL that payloads are POSTed to
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub description: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateWebhookInput {
+    pub url: Option<String>,
+    /// Replaces the webhook's events when given
+    pub events: Option<Vec<WebhookEvent>>,
+    pub description: Option<String>,
+    /// Inactive webhooks get no new deliveries, and their pending ones wait until they're
+    /// reactivated
+    pub is_active: Option<bool>,
+}
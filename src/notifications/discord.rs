@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use serde_json::json;
+use sqlx::PgPool;
+use std::env;
+
+use super::{MissedUpdates, Notifier};
+use crate::models::notification::NotificationChannel;
+
+/// Posts to a Discord channel through an incoming webhook, mentioning the member if their
+/// Discord ID is known
+pub struct DiscordNotifier {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl DiscordNotifier {
+    pub fn from_env() -> Result<Self, String> {
+        let url = env::var("DISCORD_WEBHOOK_URL")
+            .map_err(|_| "DISCORD_WEBHOOK_URL must be set for discord notifications")?;
+
+        Ok(Self {
+            url,
+            http: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Discord
+    }
+
+    async fn notify(&self, _pool: &PgPool, missed: &MissedUpdates) -> Result<(), String> {
+        // Mentions need the numeric user ID, not the username
+        let user_id = missed
+            .member
+            .discord_id
+            .as_deref()
+            .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()));
+        let who = match user_id {
+            Some(id) => format!("<@{}>", id),
+            None => missed.member.name.clone(),
+        };
+
+        let response = self
+            .http
+            .post(&self.url)
+            .json(&json!({
+                "content": format!(
+                    "{} has missed {} status updates in a row.",
+                    who, missed.misses
+                ),
+                "allowed_mentions": { "users": user_id.into_iter().collect::<Vec<_>>() },
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to post to Discord: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Discord answered {}", response.status()));
+        }
+
+        Ok(())
+    }
+}
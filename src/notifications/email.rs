@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use super::{MissedUpdates, Notifier};
+use crate::mailer::{Email, Mailer};
+use crate::models::notification::NotificationChannel;
+
+/// Emails the member at their primary address
+pub struct EmailNotifier {
+    mailer: Arc<dyn Mailer>,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Arc<dyn Mailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Email
+    }
+
+    async fn notify(&self, _pool: &PgPool, missed: &MissedUpdates) -> Result<(), String> {
+        let last_sent = match missed.last_sent_date {
+            Some(date) => format!("Your last update was on {}.", date.format("%d %B %Y")),
+            None => "We haven't received any updates from you yet.".to_string(),
+        };
+
+        self.mailer
+            .send(Email {
+                to: missed.member.email.clone(),
+                subject: format!("You've missed {} status updates", missed.misses),
+                body: format!(
+                    "Hi {},\n\nYou've missed {} status updates in a row, not counting breaks. \
+                    {}\n\nIf something's keeping you from sending them, let your mentor know.",
+                    missed.member.name, missed.misses, last_sent
+                ),
+            })
+            .await
+    }
+}
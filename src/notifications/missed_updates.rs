@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate};
+use sqlx::{FromRow, PgPool};
+use std::env;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::{MissedUpdates, Notifier};
+use crate::daily_task::today;
+use crate::mailer::Mailer;
+use crate::models::member::{Member, ACTIVE_MEMBER_FILTER};
+use crate::models::notification::NotificationChannel;
+use crate::scheduler::Job;
+
+const DEFAULT_THRESHOLDS: &str = "1,3,7";
+
+#[derive(FromRow)]
+struct MissStreak {
+    member_id: i32,
+    last_sent_date: Option<NaiveDate>,
+    misses: i64,
+    /// Highest threshold already notified since `last_sent_date`
+    notified: i64,
+}
+
+/// Notifies members whose consecutive missed status updates, not counting breaks, reached
+/// one of the thresholds since they were last notified. Runs late in the morning, once the
+/// status update bot has marked the previous night's updates.
+pub struct MissedUpdateJob {
+    notifiers: Vec<Arc<dyn Notifier>>,
+    /// Ascending, without duplicates
+    thresholds: Vec<i64>,
+}
+
+impl MissedUpdateJob {
+    pub fn new(notifiers: Vec<Arc<dyn Notifier>>, mut thresholds: Vec<i64>) -> Self {
+        thresholds.sort_unstable();
+        thresholds.dedup();
+
+        Self {
+            notifiers,
+            thresholds,
+        }
+    }
+
+    /// Configured by `MISSED_UPDATE_THRESHOLDS`, comma-separated days, and the channels
+    /// of [`super::notifiers_from_env`]
+    pub fn from_env(mailer: Arc<dyn Mailer>) -> Result<Self, String> {
+        let thresholds = env::var("MISSED_UPDATE_THRESHOLDS")
+            .unwrap_or_else(|_| DEFAULT_THRESHOLDS.to_string())
+            .split(',')
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| match value.trim().parse() {
+                Ok(days) if days > 0 => Ok(days),
+                _ => Err(format!("Invalid missed update threshold {:?}", value)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(super::notifiers_from_env(mailer)?, thresholds))
+    }
+
+    /// Send a notification through every channel the member hasn't opted out of, recording
+    /// each attempt. Returns whether anything was sent.
+    async fn notify(
+        &self,
+        pool: &PgPool,
+        missed: &MissedUpdates,
+        opted_out: &[NotificationChannel],
+    ) -> Result<bool, String> {
+        let mut sent = false;
+
+        for notifier in &self.notifiers {
+            let channel = notifier.channel();
+            if opted_out.contains(&channel) {
+                continue;
+            }
+
+            let error = notifier.notify(pool, missed).await.err();
+            if let Some(e) = &error {
+                warn!(
+                    "Failed to notify member {} through {}: {}",
+                    missed.member.member_id,
+                    channel.as_str(),
+                    e
+                );
+            }
+            sent |= error.is_none();
+
+            sqlx::query(
+                "INSERT INTO MissedUpdateNotification
+                    (member_id, channel, threshold, misses, last_sent_date, error)
+                VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(missed.member.member_id)
+            .bind(channel)
+            .bind(missed.threshold as i32)
+            .bind(missed.misses as i32)
+            .bind(missed.last_sent_date)
+            .bind(&error)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to record notification: {}", e))?;
+        }
+
+        Ok(sent)
+    }
+}
+
+#[async_trait]
+impl Job for MissedUpdateJob {
+    fn name(&self) -> &'static str {
+        "missed_update_notifications"
+    }
+
+    fn schedule(&self) -> &'static str {
+        "30 10 * * *"
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        let Some(&lowest) = self.thresholds.first() else {
+            return Ok(());
+        };
+        if self.notifiers.is_empty() {
+            return Ok(());
+        }
+
+        let date = today() - Duration::days(1);
+        let streaks = miss_streaks(pool, date, lowest).await?;
+
+        let due: Vec<(MissStreak, i64)> = streaks
+            .into_iter()
+            .filter_map(|streak| {
+                let threshold = self
+                    .thresholds
+                    .iter()
+                    .copied()
+                    .rfind(|&threshold| threshold <= streak.misses)?;
+                (threshold > streak.notified).then_some((streak, threshold))
+            })
+            .collect();
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let member_ids: Vec<i32> = due.iter().map(|(streak, _)| streak.member_id).collect();
+        let members = sqlx::query_as::<_, Member>("SELECT * FROM Member WHERE member_id = ANY($1)")
+            .bind(&member_ids)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch members: {}", e))?;
+        let opt_outs: Vec<(i32, NotificationChannel)> = sqlx::query_as(
+            "SELECT member_id, channel FROM MissedUpdateOptOut WHERE member_id = ANY($1)",
+        )
+        .bind(&member_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch notification preferences: {}", e))?;
+
+        let mut notified = 0;
+        for (streak, threshold) in due {
+            let Some(member) = members.iter().find(|m| m.member_id == streak.member_id) else {
+                continue;
+            };
+            let opted_out: Vec<NotificationChannel> = opt_outs
+                .iter()
+                .filter(|(member_id, _)| *member_id == streak.member_id)
+                .map(|(_, channel)| *channel)
+                .collect();
+
+            let missed = MissedUpdates {
+                member: member.clone(),
+                misses: streak.misses,
+                threshold,
+                last_sent_date: streak.last_sent_date,
+            };
+            match self.notify(pool, &missed, &opted_out).await {
+                Ok(true) => notified += 1,
+                Ok(false) => {}
+                // A send that wasn't recorded is made again by the next run, so the member
+                // may be notified twice
+                Err(e) => warn!("Member {}: {}", streak.member_id, e),
+            }
+        }
+
+        info!("Notified {} members of missed status updates", notified);
+        Ok(())
+    }
+}
+
+/// Active members with at least `min_misses` consecutive missed updates up to `date`,
+/// skipping days in breaks, as in the `consecutiveMisses` field of Member
+async fn miss_streaks(
+    pool: &PgPool,
+    date: NaiveDate,
+    min_misses: i64,
+) -> Result<Vec<MissStreak>, String> {
+    sqlx::query_as::<_, MissStreak>(&format!(
+        "WITH days AS (
+            SELECT suh.member_id, suh.date, suh.is_sent
+            FROM StatusUpdateHistory suh
+            JOIN Member m ON m.member_id = suh.member_id
+            WHERE {} AND suh.date <= $1
+            AND (
+                suh.is_sent
+                OR NOT EXISTS (
+                    SELECT 1 FROM StatusBreaks sb
+                    WHERE (sb.batch IS NULL OR sb.batch = m.batch)
+                    AND suh.date BETWEEN sb.start_date AND sb.end_date
+                )
+            )
+        ),
+        last_sent AS (
+            SELECT member_id, MAX(date) FILTER (WHERE is_sent) AS last_sent_date
+            FROM days
+            GROUP BY member_id
+        ),
+        streaks AS (
+            SELECT ls.member_id, ls.last_sent_date, COUNT(*) AS misses
+            FROM last_sent ls
+            JOIN days d ON d.member_id = ls.member_id
+            WHERE NOT d.is_sent AND d.date > COALESCE(ls.last_sent_date, '-infinity'::DATE)
+            GROUP BY ls.member_id, ls.last_sent_date
+        )
+        SELECT s.member_id, s.last_sent_date, s.misses,
+            COALESCE((
+                SELECT MAX(n.threshold) FROM MissedUpdateNotification n
+                WHERE n.member_id = s.member_id
+                AND n.last_sent_date IS NOT DISTINCT FROM s.last_sent_date
+                AND n.error IS NULL
+            ), 0)::BIGINT AS notified
+        FROM streaks s
+        WHERE s.misses >= $2",
+        ACTIVE_MEMBER_FILTER
+    ))
+    .bind(date)
+    .bind(min_misses)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to count missed updates: {}", e))
+}
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::mailer::Mailer;
+use crate::models::member::Member;
+use crate::models::notification::{MissedUpdateNotification, NotificationChannel};
+
+pub mod discord;
+pub mod email;
+pub mod missed_updates;
+pub mod webhook;
+
+pub use discord::DiscordNotifier;
+pub use email::EmailNotifier;
+pub use missed_updates::MissedUpdateJob;
+pub use webhook::WebhookNotifier;
+
+/// A member whose consecutive missed status updates reached a threshold
+#[derive(Debug)]
+pub struct MissedUpdates {
+    pub member: Member,
+    pub misses: i64,
+    pub threshold: i64,
+    pub last_sent_date: Option<NaiveDate>,
+}
+
+/// A way of telling members, or the people looking after them, about missed updates
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn channel(&self) -> NotificationChannel;
+
+    async fn notify(&self, pool: &PgPool, missed: &MissedUpdates) -> Result<(), String>;
+}
+
+/// The notifiers listed in `MISSED_UPDATE_CHANNELS` (`email` by default). `discord` needs
+/// `DISCORD_WEBHOOK_URL`.
+pub fn notifiers_from_env(mailer: Arc<dyn Mailer>) -> Result<Vec<Arc<dyn Notifier>>, String> {
+    let names = env::var("MISSED_UPDATE_CHANNELS").unwrap_or_else(|_| "email".to_string());
+
+    let notifiers = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| -> Result<Arc<dyn Notifier>, String> {
+            match name {
+                "email" => Ok(Arc::new(EmailNotifier::new(mailer.clone()))),
+                "discord" => Ok(Arc::new(DiscordNotifier::from_env()?)),
+                "webhook" => Ok(Arc::new(WebhookNotifier)),
+                other => Err(format!("Unknown notification channel {:?}", other)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Notifying missed status updates through {:?}", names);
+    Ok(notifiers)
+}
+
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Channels the member gets missed update notifications through, unless they're
+    /// disabled on the server
+    pub async fn enabled_channels(
+        pool: &PgPool,
+        member_id: i32,
+    ) -> Result<Vec<NotificationChannel>, String> {
+        let opted_out: Vec<NotificationChannel> =
+            sqlx::query_scalar("SELECT channel FROM MissedUpdateOptOut WHERE member_id = $1")
+                .bind(member_id)
+                .fetch_all(pool)
+                .await
+                .map_err(|e| format!("Failed to fetch notification preferences: {}", e))?;
+
+        Ok(NotificationChannel::ALL
+            .into_iter()
+            .filter(|channel| !opted_out.contains(channel))
+            .collect())
+    }
+
+    /// Opt a member in to or out of missed update notifications through `channel`
+    pub async fn set_channel(
+        pool: &PgPool,
+        member_id: i32,
+        channel: NotificationChannel,
+        enabled: bool,
+    ) -> Result<(), String> {
+        let query = if enabled {
+            "DELETE FROM MissedUpdateOptOut WHERE member_id = $1 AND channel = $2"
+        } else {
+            "INSERT INTO MissedUpdateOptOut (member_id, channel) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING"
+        };
+
+        sqlx::query(query)
+            .bind(member_id)
+            .bind(channel)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to update notification preferences: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Sent notifications, newest first, optionally of one member
+    pub async fn history(
+        pool: &PgPool,
+        member_id: Option<i32>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<MissedUpdateNotification>, String> {
+        sqlx::query_as::<_, MissedUpdateNotification>(
+            "SELECT * FROM MissedUpdateNotification
+            WHERE $1::INT IS NULL OR member_id = $1
+            ORDER BY notification_id DESC
+            LIMIT $2 OFFSET $3",
+        )
+        .bind(member_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch notification history: {}", e))
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use serde_json::json;
+use sqlx::PgPool;
+
+use super::{MissedUpdates, Notifier};
+use crate::models::notification::NotificationChannel;
+use crate::models::webhook::WebhookEvent;
+use crate::webhooks::WebhookService;
+
+/// Queues an `updates.missed` event for the webhooks subscribed to it, which are signed and
+/// retried like every other webhook
+pub struct WebhookNotifier;
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Webhook
+    }
+
+    async fn notify(&self, pool: &PgPool, missed: &MissedUpdates) -> Result<(), String> {
+        WebhookService::enqueue(
+            pool,
+            WebhookEvent::UpdatesMissed,
+            json!({
+                "memberId": missed.member.member_id,
+                "name": missed.member.name,
+                "misses": missed.misses,
+                "threshold": missed.threshold,
+                "lastSentDate": missed.last_sent_date,
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
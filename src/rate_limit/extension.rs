@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo,
+};
+use async_graphql::{ErrorExtensionValues, ServerError, ServerResult, Value};
+
+use crate::auth::AuthContext;
+use crate::client_ip::ClientIp;
+use crate::rate_limit::{ip_subject, RateLimiter};
+
+/// Error extension holding the seconds to wait, turned into a 429 by the GraphQL handler
+pub const RETRY_AFTER_EXTENSION: &str = "retryAfter";
+
+/// Applies operation limits to root query and mutation fields, per client address and
+/// per member.
+pub struct RateLimit(pub RateLimiter);
+
+impl ExtensionFactory for RateLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RateLimitExtension(self.0.clone()))
+    }
+}
+
+struct RateLimitExtension(RateLimiter);
+
+#[async_trait::async_trait]
+impl Extension for RateLimitExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let limit = match info.parent_type {
+            "Query" | "Mutation" => self.0.operation_limit(info.name),
+            _ => None,
+        };
+        let Some(limit) = limit else {
+            return next.run(ctx, info).await;
+        };
+
+        let mut subjects = vec![ip_subject(ctx.data_opt::<ClientIp>())];
+        if let Some(user) = ctx.data_opt::<AuthContext>().and_then(|auth| auth.user()) {
+            subjects.push(format!("member:{}", user.member_id));
+        }
+
+        let rule = format!("operation:{}", info.name);
+        if let Some(retry_after) = self.0.check(&rule, &subjects, limit).await {
+            let seconds = retry_after.as_secs().max(1);
+            let mut extensions = ErrorExtensionValues::default();
+            extensions.set("code", "RATE_LIMITED");
+            extensions.set(RETRY_AFTER_EXTENSION, seconds);
+
+            let mut error = ServerError::new(
+                format!("Too many requests, try again in {} seconds", seconds),
+                None,
+            );
+            error.extensions = Some(extensions);
+            return Err(error);
+        }
+
+        next.run(ctx, info).await
+    }
+}
@@ -0,0 +1,200 @@
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::auth::AuthContext;
+use crate::client_ip::ClientIp;
+
+pub mod extension;
+pub mod store;
+
+pub use extension::RateLimit;
+pub use store::{MemoryStore, PostgresStore, RateLimitStore};
+
+const DEFAULT_ROUTE_LIMITS: &str = "/auth=30/60,/=1200/60";
+const DEFAULT_OPERATION_LIMITS: &str = "markAttendance=300/60,requestLoginLink=5/900";
+
+/// At most `count` requests per `window`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limit {
+    pub count: u32,
+    pub window: Duration,
+}
+
+impl Limit {
+    /// Parse `count/seconds`, e.g. `30/60`
+    fn parse(value: &str) -> Option<Self> {
+        let (count, seconds) = value.trim().split_once('/')?;
+        let count = count.trim().parse().ok()?;
+        let seconds: u64 = seconds.trim().parse().ok()?;
+
+        (seconds > 0).then(|| Self {
+            count,
+            window: Duration::from_secs(seconds),
+        })
+    }
+}
+
+/// Parse comma-separated `name=count/seconds` rules
+fn parse_limits(value: &str) -> Result<Vec<(String, Limit)>, String> {
+    value
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(|rule| {
+            rule.split_once('=')
+                .and_then(|(name, limit)| Some((name.trim().to_string(), Limit::parse(limit)?)))
+                .ok_or_else(|| format!("Invalid rate limit rule {:?}", rule))
+        })
+        .collect()
+}
+
+/// Limits requests per client address and per principal, i.e. session or API key. A
+/// request over either limit is rejected. Route limits apply to the longest matching path
+/// prefix, and GraphQL operation limits to root query and mutation fields.
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    routes: Vec<(String, Limit)>,
+    operations: HashMap<String, Limit>,
+}
+
+impl RateLimiter {
+    pub fn new(
+        store: Arc<dyn RateLimitStore>,
+        routes: Vec<(String, Limit)>,
+        operations: HashMap<String, Limit>,
+    ) -> Self {
+        Self {
+            store,
+            routes,
+            operations,
+        }
+    }
+
+    /// Configured by `RATE_LIMIT_STORE` (`memory` or `postgres`), `RATE_LIMIT_ROUTES` and
+    /// `RATE_LIMIT_OPERATIONS`. Setting either list to an empty value disables its limits.
+    pub fn from_env(pool: Arc<PgPool>) -> Result<Self, String> {
+        let store: Arc<dyn RateLimitStore> = match std::env::var("RATE_LIMIT_STORE")
+            .unwrap_or_else(|_| "memory".to_string())
+            .as_str()
+        {
+            "memory" => Arc::new(MemoryStore::default()),
+            "postgres" => Arc::new(PostgresStore::new(pool)),
+            other => return Err(format!("Unknown RATE_LIMIT_STORE {:?}", other)),
+        };
+
+        let routes = parse_limits(
+            &std::env::var("RATE_LIMIT_ROUTES").unwrap_or_else(|_| DEFAULT_ROUTE_LIMITS.into()),
+        )?;
+        let operations = parse_limits(
+            &std::env::var("RATE_LIMIT_OPERATIONS")
+                .unwrap_or_else(|_| DEFAULT_OPERATION_LIMITS.into()),
+        )?;
+
+        info!(
+            "Rate limiting {} routes and {} GraphQL operations",
+            routes.len(),
+            operations.len()
+        );
+
+        Ok(Self::new(store, routes, operations.into_iter().collect()))
+    }
+
+    fn route_limit(&self, path: &str) -> Option<(&str, Limit)> {
+        self.routes
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, limit)| (prefix.as_str(), *limit))
+    }
+
+    pub fn operation_limit(&self, operation: &str) -> Option<Limit> {
+        self.operations.get(operation).copied()
+    }
+
+    /// Count a hit on `rule` for each of `subjects`, in order. Returns how long to wait if
+    /// one of them is over `limit`, without counting the rest, so that a client already
+    /// over its limit can't fill the store with further keys. Fails open if the store
+    /// can't be reached.
+    pub async fn check(&self, rule: &str, subjects: &[String], limit: Limit) -> Option<Duration> {
+        for subject in subjects {
+            let key = format!("{}|{}", rule, subject);
+            match self.store.hit(&key, limit.window).await {
+                Ok((count, remaining)) if count > limit.count => return Some(remaining),
+                Ok(_) => {}
+                Err(e) => warn!("{}", e),
+            }
+        }
+
+        None
+    }
+}
+
+pub fn ip_subject(client_ip: Option<&ClientIp>) -> String {
+    match client_ip.and_then(|ip| ip.0) {
+        Some(ip) => format!("ip:{}", ip),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+/// `429 Too Many Requests` with a `Retry-After` header
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs().max(1);
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, seconds.to_string())],
+        format!("Too many requests, try again in {} seconds", seconds),
+    )
+        .into_response()
+}
+
+/// Applies route limits per client address. Runs before authentication, so that floods of
+/// bogus credentials are rejected without checking them.
+pub async fn rate_limit_middleware(limiter: RateLimiter, request: Request, next: Next) -> Response {
+    let Some((prefix, limit)) = limiter.route_limit(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let subjects = [ip_subject(request.extensions().get::<ClientIp>())];
+    let rule = format!("route:{}", prefix);
+    match limiter.check(&rule, &subjects, limit).await {
+        Some(retry_after) => too_many_requests(retry_after),
+        None => next.run(request).await,
+    }
+}
+
+/// Applies route limits per member or bot. Runs after authentication, so that only valid
+/// credentials get a key of their own.
+pub async fn principal_rate_limit_middleware(
+    limiter: RateLimiter,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((prefix, limit)) = limiter.route_limit(request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let Some(member_id) = request
+        .extensions()
+        .get::<AuthContext>()
+        .and_then(|auth| auth.user())
+        .map(|user| user.member_id)
+    else {
+        return next.run(request).await;
+    };
+
+    let subjects = [format!("member:{}", member_id)];
+    let rule = format!("route:{}", prefix);
+    match limiter.check(&rule, &subjects, limit).await {
+        Some(retry_after) => too_many_requests(retry_after),
+        None => next.run(request).await,
+    }
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Keys the memory store holds at most. When it is full, the window that ends soonest is
+/// dropped to make room.
+const MAX_KEYS: usize = 100_000;
+
+/// Counts hits per key in fixed windows
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Count a hit on `key`, starting a new `window` if the last one ended. Returns the
+    /// hits in the current window and the time until it ends.
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String>;
+}
+
+/// Keeps counts in this process. Each instance counts separately.
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    counters: HashMap<String, (u32, Instant)>,
+    /// Every key by the end of its window, soonest first
+    ends: BTreeSet<(Instant, String)>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String> {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("Rate limit lock poisoned");
+
+        while state
+            .ends
+            .first()
+            .is_some_and(|(ends_at, _)| *ends_at <= now)
+        {
+            if let Some((_, ended)) = state.ends.pop_first() {
+                state.counters.remove(&ended);
+            }
+        }
+
+        if let Some((count, ends_at)) = state.counters.get_mut(key) {
+            *count += 1;
+            return Ok((*count, *ends_at - now));
+        }
+
+        if state.counters.len() >= MAX_KEYS {
+            if let Some((_, evicted)) = state.ends.pop_first() {
+                state.counters.remove(&evicted);
+            }
+        }
+
+        let ends_at = now + window;
+        state.counters.insert(key.to_string(), (1, ends_at));
+        state.ends.insert((ends_at, key.to_string()));
+
+        Ok((1, window))
+    }
+}
+
+/// Keeps counts in `RateLimitCounter`, so that limits hold across instances
+pub struct PostgresStore {
+    pool: Arc<PgPool>,
+}
+
+impl PostgresStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Delete counters whose window has ended. Returns the number deleted.
+    pub async fn cleanup_expired(pool: &PgPool) -> Result<u64, String> {
+        let result = sqlx::query("DELETE FROM RateLimitCounter WHERE expires_at <= NOW()")
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to clean up rate limit counters: {}", e))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresStore {
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u32, Duration), String> {
+        let window = chrono::Duration::from_std(window)
+            .map_err(|e| format!("Invalid rate limit window: {}", e))?;
+
+        let (count, remaining_ms): (i32, i64) = sqlx::query_as(
+            "INSERT INTO RateLimitCounter (key, count, expires_at)
+            VALUES ($1, 1, NOW() + $2)
+            ON CONFLICT (key) DO UPDATE SET
+                count = CASE WHEN RateLimitCounter.expires_at <= NOW() THEN 1
+                    ELSE RateLimitCounter.count + 1 END,
+                expires_at = CASE WHEN RateLimitCounter.expires_at <= NOW()
+                    THEN EXCLUDED.expires_at ELSE RateLimitCounter.expires_at END
+            RETURNING count,
+                (EXTRACT(EPOCH FROM expires_at - NOW()) * 1000)::BIGINT",
+        )
+        .bind(key)
+        .bind(window)
+        .fetch_one(self.pool.as_ref())
+        .await
+        .map_err(|e| format!("Failed to count request: {}", e))?;
+
+        Ok((
+            count as u32,
+            Duration::from_millis(remaining_ms.max(0) as u64),
+        ))
+    }
+}
@@ -1,69 +1,138 @@
-use async_graphql::{http::GraphiQLSource, EmptySubscription, Schema};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::{
+    http::{GraphiQLSource, ALL_WEBSOCKET_PROTOCOLS},
+    Data, Schema, Value,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    extract::{Extension, Query as AxumQuery, State},
+    extract::{ws::WebSocketUpgrade, Extension, Form, Path, Query as AxumQuery, State},
     http::{header, StatusCode},
     middleware,
-    response::{Html, IntoResponse, Redirect},
+    response::{AppendHeaders, Html, IntoResponse, Redirect, Response},
     routing::{get, post},
     Router,
 };
-use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 
+use crate::audit::AuditService;
 use crate::auth::auth_service::AuthService;
+use crate::auth::identity::{IdentityProviders, EMAIL_LOGIN_PATH};
+use crate::auth::magic_link::MagicLinkService;
 use crate::auth::middleware::auth_middleware;
-use crate::auth::oauth::GitHubOAuthService;
-use crate::auth::session::SessionService;
+use crate::auth::session::{session_cookie, SessionService, SESSION_DURATION_DAYS};
 use crate::auth::AuthContext;
-use crate::graphql::{Mutation, Query};
+use crate::client_ip::{client_ip_middleware, ClientIp};
+use crate::graphql::{Mutation, Query, Subscription};
+use crate::models::audit::AuditEvent;
+use crate::models::member::Member;
+use crate::rate_limit::extension::RETRY_AFTER_EXTENSION;
+use crate::rate_limit::{principal_rate_limit_middleware, rate_limit_middleware, RateLimiter};
 use crate::Config;
 
 #[derive(Clone)]
 struct AppState {
-    schema: Schema<Query, Mutation, EmptySubscription>,
+    schema: Schema<Query, Mutation, Subscription>,
     pool: Arc<PgPool>,
     config: Config,
+    providers: IdentityProviders,
 }
 
 async fn graphql_handler(
     State(state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
+    Extension(client_ip): Extension<ClientIp>,
     req: GraphQLRequest,
-) -> GraphQLResponse {
-    state
+) -> Response {
+    let response = state
         .schema
-        .execute(req.into_inner().data(auth_context))
-        .await
-        .into()
+        .execute(req.into_inner().data(auth_context).data(client_ip))
+        .await;
+
+    // Rate limited operations are reported as 429s, not just GraphQL errors
+    let retry_after = response.errors.iter().find_map(|error| {
+        match error.extensions.as_ref()?.get(RETRY_AFTER_EXTENSION)? {
+            Value::Number(seconds) => seconds.as_u64(),
+            _ => None,
+        }
+    });
+
+    match retry_after {
+        Some(seconds) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, seconds.to_string())],
+            GraphQLResponse::from(response),
+        )
+            .into_response(),
+        None => GraphQLResponse::from(response).into_response(),
+    }
+}
+
+/// Serves subscriptions as the member or bot that opened the connection
+async fn graphql_ws_handler(
+    State(state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Extension(client_ip): Extension<ClientIp>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let mut data = Data::default();
+    data.insert(auth_context);
+    data.insert(client_ip);
+
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, state.schema, protocol)
+                .with_data(data)
+                .serve()
+        })
 }
 
 pub fn setup_router(
-    schema: Schema<Query, Mutation, EmptySubscription>,
+    schema: Schema<Query, Mutation, Subscription>,
     cors: CorsLayer,
     config: Config,
     pool: Arc<PgPool>,
+    providers: IdentityProviders,
+    rate_limiter: RateLimiter,
 ) -> Router {
     let pool_for_middleware = pool.clone();
+    let trust_forwarded_for = config.trust_forwarded_for;
     let app_state = AppState {
         schema,
         pool,
         config: config.clone(),
+        providers,
     };
 
     let router = Router::new()
         .route("/", post(graphql_handler))
-        .route("/auth/github", get(github_oauth_init))
-        .route("/auth/github/callback", get(github_oauth_callback))
+        .route("/ws", get(graphql_ws_handler))
+        .route(
+            "/auth/email/callback",
+            get(email_login_confirm).post(email_login_callback),
+        )
+        .route("/auth/{provider}", get(oauth_init))
+        .route("/auth/{provider}/callback", get(oauth_callback))
         .route("/graphiql", get(graphiql).post(graphql_handler));
 
+    let principal_limiter = rate_limiter.clone();
     router
+        .layer(middleware::from_fn(move |req, next| {
+            principal_rate_limit_middleware(principal_limiter.clone(), req, next)
+        }))
         .layer(middleware::from_fn(move |req, next| {
             auth_middleware(pool_for_middleware.clone(), req, next)
         }))
+        .layer(middleware::from_fn(move |req, next| {
+            rate_limit_middleware(rate_limiter.clone(), req, next)
+        }))
+        .layer(middleware::from_fn(move |req, next| {
+            client_ip_middleware(trust_forwarded_for, req, next)
+        }))
         .layer(cors)
         .with_state(app_state)
 }
@@ -77,57 +146,259 @@ async fn graphiql() -> impl IntoResponse {
     )
 }
 
-// OAuth handlers
+/// Log in as `member`: set the session cookie and redirect to the frontend, clearing any
+/// leftover OAuth cookies.
+async fn start_session(
+    state: &AppState,
+    member_id: i32,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let session_token = SessionService::create_session(state.pool.as_ref(), member_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create session: {}", e),
+            )
+        })?;
 
-/// Initiates GitHub OAuth flow
-async fn github_oauth_init() -> Result<Redirect, String> {
-    let oauth_service =
-        GitHubOAuthService::new().map_err(|e| format!("Failed to initialize OAuth: {}", e))?;
+    let cookie = session_cookie(
+        &state.config,
+        session_token,
+        time::Duration::days(SESSION_DURATION_DAYS),
+    );
 
-    let (auth_url, _csrf_token) = oauth_service.get_authorization_url();
+    let mut cookies = vec![cookie.to_string()];
+    for name in [STATE_COOKIE, INVITE_COOKIE, LINK_COOKIE] {
+        let clear = Cookie::build((name, ""))
+            .path("/auth")
+            .max_age(time::Duration::ZERO)
+            .build();
+        cookies.push(clear.to_string());
+    }
 
-    Ok(Redirect::temporary(&auth_url))
+    // Redirect to frontend with cookie
+    Ok((
+        AppendHeaders(cookies.into_iter().map(|c| (header::SET_COOKIE, c))),
+        Redirect::to(&state.config.frontend_url),
+    ))
 }
 
+// Email login handlers
+
 #[derive(Deserialize)]
-struct OAuthCallbackQuery {
-    code: String,
-    // In a production system, this state variable should be populated
-    // and verified using server side cookies. For now, we'll ignore it.
-    #[allow(dead_code)]
-    state: Option<String>,
+struct EmailLoginQuery {
+    token: String,
 }
 
-/// GitHub OAuth callback handler - completes authentication and sets session cookie
-async fn github_oauth_callback(
+/// Landing page of the emailed link. Mail scanners open links on their own, so the token
+/// is only used when the member submits the form.
+async fn email_login_confirm(AxumQuery(query): AxumQuery<EmailLoginQuery>) -> Html<String> {
+    let token: String = query
+        .token
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><title>Log in to Root</title></head>
+<body>
+<form method="post">
+<input type="hidden" name="token" value="{}">
+<button type="submit">Log in to Root</button>
+</form>
+</body>
+</html>"#,
+        token
+    ))
+}
+
+/// Exchanges an emailed login token for a session
+async fn email_login_callback(
     State(state): State<AppState>,
-    AxumQuery(query): AxumQuery<OAuthCallbackQuery>,
+    Extension(client_ip): Extension<ClientIp>,
+    Form(form): Form<EmailLoginQuery>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let member = AuthService::handle_github_callback(state.pool.as_ref(), query.code)
+    let result = MagicLinkService::redeem(state.pool.as_ref(), &form.token)
         .await
-        .map_err(|e| (StatusCode::UNAUTHORIZED, format!("OAuth failed: {}", e)))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        .and_then(|member| {
+            member.ok_or((
+                StatusCode::UNAUTHORIZED,
+                "This login link is invalid, expired or was already used".to_string(),
+            ))
+        });
 
-    let session_token = SessionService::create_session(state.pool.as_ref(), member.member_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create session: {}", e),
-            )
-        })?;
+    audit_login(&state, &client_ip, EMAIL_LOGIN_PATH, &result, false).await;
+    let member = result?;
+
+    start_session(&state, member.member_id).await
+}
+
+/// Record a login attempt through `provider` in the audit log
+async fn audit_login(
+    state: &AppState,
+    client_ip: &ClientIp,
+    provider: &str,
+    result: &Result<Member, (StatusCode, String)>,
+    link: bool,
+) {
+    let mut entry = AuditService::entry(None, Some(client_ip));
+    entry.operation = Some(provider.to_string());
+    if link {
+        entry.arguments = Some(serde_json::json!({ "link": true }));
+    }
+
+    let event = match result {
+        Ok(member) => {
+            entry.actor_id = Some(member.member_id);
+            entry.actor_role = Some(member.role);
+            AuditEvent::Login
+        }
+        Err((_, error)) => {
+            entry.error = Some(error.clone());
+            AuditEvent::LoginFailed
+        }
+    };
+
+    AuditService::try_record(state.pool.as_ref(), event, entry).await;
+}
+
+// OAuth handlers
+
+const INVITE_COOKIE: &str = "invite_code";
+const STATE_COOKIE: &str = "oauth_state";
+const LINK_COOKIE: &str = "oauth_link";
 
-    let cookie = Cookie::build(("session_token", session_token))
-        .path("/")
+#[derive(Deserialize)]
+struct OAuthInitQuery {
+    /// One-time code from an admin, linking the login to an existing member
+    invite: Option<String>,
+    /// Link the account to the logged in member instead of logging in
+    #[serde(default)]
+    link: bool,
+}
+
+/// Short-lived cookie that carries login state until the provider redirects back
+fn oauth_cookie(config: &Config, name: &'static str, value: String) -> String {
+    Cookie::build((name, value))
+        .path("/auth")
         .http_only(true)
-        .secure(state.config.env != "development")
-        .domain(state.config.hostname)
+        .secure(config.env != "development")
         .same_site(SameSite::Lax)
-        .max_age(time::Duration::days(30))
-        .build();
+        .max_age(time::Duration::minutes(15))
+        .build()
+        .to_string()
+}
+
+/// Initiates the OAuth flow of an identity provider
+async fn oauth_init(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    AxumQuery(query): AxumQuery<OAuthInitQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let provider = state.providers.get(&provider).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Unknown identity provider {}", provider),
+    ))?;
+
+    let (auth_url, csrf_token) = provider.authorization_url();
+
+    let mut cookies = vec![oauth_cookie(
+        &state.config,
+        STATE_COOKIE,
+        csrf_token.secret().clone(),
+    )];
+    if let Some(invite) = query.invite {
+        cookies.push(oauth_cookie(&state.config, INVITE_COOKIE, invite));
+    }
+    if query.link {
+        cookies.push(oauth_cookie(&state.config, LINK_COOKIE, "1".to_string()));
+    }
 
-    // Redirect to frontend with cookie
     Ok((
-        [(header::SET_COOKIE, cookie.to_string())],
-        Redirect::to(&state.config.frontend_url),
+        AppendHeaders(cookies.into_iter().map(|c| (header::SET_COOKIE, c))),
+        Redirect::temporary(&auth_url),
     ))
 }
+
+#[derive(Deserialize)]
+struct OAuthCallbackQuery {
+    code: String,
+    state: Option<String>,
+}
+
+/// OAuth callback handler - completes authentication and sets session cookie
+async fn oauth_callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Extension(auth_context): Extension<AuthContext>,
+    Extension(client_ip): Extension<ClientIp>,
+    AxumQuery(query): AxumQuery<OAuthCallbackQuery>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let provider = state.providers.get(&provider).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Unknown identity provider {}", provider),
+    ))?;
+
+    let expected_state = jar.get(STATE_COOKIE).map(|c| c.value());
+    if expected_state.is_none() || expected_state != query.state.as_deref() {
+        let error = (
+            StatusCode::BAD_REQUEST,
+            "OAuth state mismatch, please log in again".to_string(),
+        );
+        audit_login(
+            &state,
+            &client_ip,
+            provider.name(),
+            &Err(error.clone()),
+            false,
+        )
+        .await;
+        return Err(error);
+    }
+
+    let link_to = match jar.get(LINK_COOKIE) {
+        Some(_) if auth_context.is_impersonating() => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Accounts can't be linked while impersonating".to_string(),
+            ))
+        }
+        Some(_) => Some(
+            auth_context
+                .user()
+                .filter(|_| !auth_context.is_bot())
+                .ok_or((
+                    StatusCode::UNAUTHORIZED,
+                    "Log in before linking an account".to_string(),
+                ))?,
+        ),
+        None => None,
+    };
+
+    let invite_code = jar.get(INVITE_COOKIE).map(|c| c.value().to_string());
+    let result = AuthService::handle_callback(
+        state.pool.as_ref(),
+        provider.as_ref(),
+        query.code,
+        invite_code,
+        link_to,
+    )
+    .await
+    .map_err(|e| (StatusCode::UNAUTHORIZED, format!("OAuth failed: {}", e)));
+
+    audit_login(
+        &state,
+        &client_ip,
+        provider.name(),
+        &result,
+        link_to.is_some(),
+    )
+    .await;
+    let member = result?;
+
+    start_session(&state, member.member_id).await
+}
@@ -0,0 +1,395 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Asia::Kolkata;
+use rand::Rng;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::models::scheduler::JobStatus;
+
+pub mod schedule;
+
+pub use schedule::Schedule;
+
+/// Delay before the first retry of a failed run. Doubles with every further attempt.
+const BASE_RETRY_DELAY_SECS: u64 = 30;
+
+/// How long a claimed run is protected from other instances. Renewed before every attempt,
+/// so it only needs to cover a single attempt and the backoff before the next one.
+const LEASE_DURATION_SECS: i64 = 10 * 60;
+
+/// A unit of work run periodically by the [`Scheduler`].
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Unique name, used as the key for the job's persisted state.
+    fn name(&self) -> &'static str;
+
+    /// Cron-like expression evaluated in IST, see [`Schedule`].
+    fn schedule(&self) -> &'static str;
+
+    /// How many times a failed run is retried before it is marked as failed.
+    fn max_retries(&self) -> u32 {
+        3
+    }
+
+    /// Whether the job should also run once as soon as the scheduler starts,
+    /// e.g. to catch up on work missed while the server was down. Startup runs happen
+    /// one at a time, in the order the jobs were registered.
+    fn run_on_startup(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String>;
+}
+
+struct RegisteredJob {
+    job: Arc<dyn Job>,
+    schedule: Schedule,
+}
+
+/// Runs registered jobs on their schedules, retrying failures with exponential backoff
+/// and persisting their state in the `ScheduledJob` and `ScheduledJobRun` tables.
+///
+/// When several instances of Root share a database, each scheduled run is claimed through
+/// a lease on the job's `ScheduledJob` row, so only one instance executes it.
+pub struct Scheduler {
+    pool: Arc<PgPool>,
+    instance_id: Arc<str>,
+    jobs: Vec<RegisteredJob>,
+}
+
+impl Scheduler {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        let instance_id = format!(
+            "{}-{:08x}",
+            std::process::id(),
+            rand::thread_rng().gen::<u32>()
+        );
+
+        Self {
+            pool,
+            instance_id: instance_id.into(),
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Add a job. Panics if the job's schedule is invalid, since schedules are hardcoded.
+    pub fn register(mut self, job: impl Job + 'static) -> Self {
+        let schedule = Schedule::parse(job.schedule())
+            .unwrap_or_else(|e| panic!("Invalid schedule for job {}: {}", job.name(), e));
+
+        self.jobs.push(RegisteredJob {
+            job: Arc::new(job),
+            schedule,
+        });
+        self
+    }
+
+    /// Spawn a task for each registered job. Returns immediately.
+    pub async fn start(self) {
+        // Each startup run waits for the one registered before it
+        let mut previous_startup: Option<oneshot::Receiver<()>> = None;
+
+        for registered in self.jobs {
+            if let Err(e) = register_job_state(&self.pool, &registered).await {
+                error!(
+                    "Failed to persist state for job {}: {}",
+                    registered.job.name(),
+                    e
+                );
+            }
+
+            let startup = registered.job.run_on_startup().then(|| {
+                let (done, next) = oneshot::channel();
+                (previous_startup.replace(next), done)
+            });
+
+            let pool = self.pool.clone();
+            let instance_id = self.instance_id.clone();
+            tokio::task::spawn(async move {
+                run_job_loop(pool, instance_id, registered, startup).await;
+            });
+        }
+    }
+}
+
+fn now() -> NaiveDateTime {
+    Utc::now().with_timezone(&Kolkata).naive_local()
+}
+
+async fn register_job_state(pool: &PgPool, registered: &RegisteredJob) -> Result<(), String> {
+    // A job left as 'Running' without a live lease was interrupted by a shutdown or crash.
+    // If the lease is still live, another instance is running it right now.
+    sqlx::query(
+        "INSERT INTO ScheduledJob (job_name, schedule)
+        VALUES ($1, $2)
+        ON CONFLICT (job_name) DO UPDATE SET
+            schedule = $2,
+            status = CASE
+                WHEN ScheduledJob.status = 'Running'
+                    AND (ScheduledJob.locked_until IS NULL OR ScheduledJob.locked_until < NOW())
+                THEN 'Failed'::job_status
+                ELSE ScheduledJob.status END,
+            updated_at = NOW()",
+    )
+    .bind(registered.job.name())
+    .bind(registered.schedule.to_string())
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to register job: {}", e))?;
+
+    Ok(())
+}
+
+/// `startup` is given for jobs that run on startup: the previous startup run to wait for,
+/// and where to signal that this one is done.
+async fn run_job_loop(
+    pool: Arc<PgPool>,
+    instance_id: Arc<str>,
+    registered: RegisteredJob,
+    startup: Option<(Option<oneshot::Receiver<()>>, oneshot::Sender<()>)>,
+) {
+    let name = registered.job.name();
+
+    if let Some((previous, done)) = startup {
+        if let Some(previous) = previous {
+            // An error only means the previous job's task ended early
+            let _ = previous.await;
+        }
+        info!("Running job {} on startup", name);
+        run_claimed(&pool, &instance_id, &registered, now()).await;
+        let _ = done.send(());
+    }
+
+    loop {
+        let current = Utc::now().with_timezone(&Kolkata);
+        let Some(next_run) = registered.schedule.next_after(&current) else {
+            error!(
+                "Schedule '{}' for job {} never matches, stopping it",
+                registered.schedule, name
+            );
+            return;
+        };
+
+        if let Err(e) = sqlx::query(
+            "UPDATE ScheduledJob SET next_run_at = $2, updated_at = NOW() WHERE job_name = $1",
+        )
+        .bind(name)
+        .bind(next_run.naive_local())
+        .execute(pool.as_ref())
+        .await
+        {
+            warn!("Failed to persist next run for job {}: {:?}", name, e);
+        }
+
+        let duration = next_run.signed_duration_since(current);
+        debug!("Job {} sleeping for {}s", name, duration.num_seconds());
+        sleep(duration.to_std().unwrap_or_default()).await;
+
+        run_claimed(&pool, &instance_id, &registered, next_run.naive_local()).await;
+    }
+}
+
+/// Run the job for the slot at `scheduled_for`, unless another instance has already claimed it.
+async fn run_claimed(
+    pool: &Arc<PgPool>,
+    instance_id: &str,
+    registered: &RegisteredJob,
+    scheduled_for: NaiveDateTime,
+) {
+    let name = registered.job.name();
+
+    match acquire_lease(pool, name, instance_id, scheduled_for).await {
+        Ok(true) => {}
+        Ok(false) => {
+            info!(
+                "Skipping job {} for {}, claimed by another instance",
+                name, scheduled_for
+            );
+            return;
+        }
+        Err(e) => {
+            error!("Skipping job {}: {}", name, e);
+            return;
+        }
+    }
+
+    run_with_retries(pool, instance_id, registered).await;
+
+    // The job itself runs in a separate task, so this is reached even if it panicked.
+    if let Err(e) = release_lease(pool, name, instance_id).await {
+        warn!("{}. It will expire on its own.", e);
+    }
+}
+
+/// Claim the run at `scheduled_for`. Fails if that run (or a later one) was already claimed,
+/// or if another instance holds a live lease.
+async fn acquire_lease(
+    pool: &PgPool,
+    name: &str,
+    instance_id: &str,
+    scheduled_for: NaiveDateTime,
+) -> Result<bool, String> {
+    let result = sqlx::query(
+        "UPDATE ScheduledJob SET
+            locked_by = $2,
+            locked_until = NOW() + $4 * INTERVAL '1 second',
+            claimed_run_at = $3
+        WHERE job_name = $1
+        AND (claimed_run_at IS NULL OR claimed_run_at < $3)
+        AND (locked_until IS NULL OR locked_until < NOW())",
+    )
+    .bind(name)
+    .bind(instance_id)
+    .bind(scheduled_for)
+    .bind(LEASE_DURATION_SECS)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to acquire lease: {}", e))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+async fn renew_lease(pool: &PgPool, name: &str, instance_id: &str) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE ScheduledJob SET locked_until = NOW() + $3 * INTERVAL '1 second'
+        WHERE job_name = $1 AND locked_by = $2",
+    )
+    .bind(name)
+    .bind(instance_id)
+    .bind(LEASE_DURATION_SECS)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to renew lease for job {}: {}", name, e))?;
+
+    Ok(())
+}
+
+async fn release_lease(pool: &PgPool, name: &str, instance_id: &str) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE ScheduledJob SET locked_by = NULL, locked_until = NULL
+        WHERE job_name = $1 AND locked_by = $2",
+    )
+    .bind(name)
+    .bind(instance_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to release lease for job {}: {}", name, e))?;
+
+    Ok(())
+}
+
+async fn run_with_retries(pool: &Arc<PgPool>, instance_id: &str, registered: &RegisteredJob) {
+    let name = registered.job.name();
+    let max_attempts = registered.job.max_retries() + 1;
+
+    for attempt in 1..=max_attempts {
+        if let Err(e) = renew_lease(pool, name, instance_id).await {
+            warn!("{}", e);
+        }
+
+        let run_id = start_run(pool, name, attempt).await;
+
+        // Run in a separate task so that a panicking job is reported as a failure
+        // instead of taking the scheduler loop down with it.
+        let job = registered.job.clone();
+        let job_pool = pool.clone();
+        let result = tokio::task::spawn(async move { job.run(&job_pool).await })
+            .await
+            .unwrap_or_else(|e| Err(format!("Job panicked: {}", e)));
+
+        finish_run(pool, name, run_id, &result).await;
+
+        match result {
+            Ok(()) => {
+                info!("Job {} succeeded", name);
+                return;
+            }
+            Err(e) if attempt < max_attempts => {
+                let delay = BASE_RETRY_DELAY_SECS * 2u64.pow(attempt - 1);
+                warn!(
+                    "Job {} failed on attempt {}/{}: {}. Retrying in {}s",
+                    name, attempt, max_attempts, e, delay
+                );
+                sleep(std::time::Duration::from_secs(delay)).await;
+            }
+            Err(e) => {
+                error!("Job {} failed after {} attempts: {}", name, attempt, e);
+            }
+        }
+    }
+}
+
+async fn start_run(pool: &PgPool, name: &str, attempt: u32) -> Option<i32> {
+    let started_at = now();
+
+    if let Err(e) = sqlx::query(
+        "UPDATE ScheduledJob SET status = 'Running', last_run_at = $2, updated_at = NOW()
+        WHERE job_name = $1",
+    )
+    .bind(name)
+    .bind(started_at)
+    .execute(pool)
+    .await
+    {
+        warn!("Failed to update status of job {}: {:?}", name, e);
+    }
+
+    sqlx::query_scalar(
+        "INSERT INTO ScheduledJobRun (job_name, attempt, started_at)
+        VALUES ($1, $2, $3)
+        RETURNING run_id",
+    )
+    .bind(name)
+    .bind(attempt as i32)
+    .bind(started_at)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| warn!("Failed to record run of job {}: {:?}", name, e))
+    .ok()
+}
+
+async fn finish_run(pool: &PgPool, name: &str, run_id: Option<i32>, result: &Result<(), String>) {
+    let finished_at = now();
+    let (status, error) = match result {
+        Ok(()) => (JobStatus::Succeeded, None),
+        Err(e) => (JobStatus::Failed, Some(e.as_str())),
+    };
+
+    if let Err(e) = sqlx::query(
+        "UPDATE ScheduledJob SET
+            status = $2,
+            last_error = $3,
+            last_success_at = CASE WHEN $2 = 'Succeeded'::job_status THEN $4 ELSE last_success_at END,
+            updated_at = NOW()
+        WHERE job_name = $1",
+    )
+    .bind(name)
+    .bind(status)
+    .bind(error)
+    .bind(finished_at)
+    .execute(pool)
+    .await
+    {
+        warn!("Failed to update status of job {}: {:?}", name, e);
+    }
+
+    if let Some(run_id) = run_id {
+        if let Err(e) = sqlx::query(
+            "UPDATE ScheduledJobRun SET status = $2, error = $3, finished_at = $4
+            WHERE run_id = $1",
+        )
+        .bind(run_id)
+        .bind(status)
+        .bind(error)
+        .bind(finished_at)
+        .execute(pool)
+        .await
+        {
+            warn!("Failed to record result of job {}: {:?}", name, e);
+        }
+    }
+}
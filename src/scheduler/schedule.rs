@@ -0,0 +1,290 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone};
+use std::fmt;
+
+/// How far ahead `Schedule::next_after` searches before giving up. Covers
+/// expressions like `0 0 29 2 *` which only match once every four years.
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+/// A cron-like schedule made up of five space separated fields:
+/// `minute hour day-of-month month day-of-week`.
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), lists (`1,15`) and
+/// steps (`*/15`, `0-30/10`). Day-of-week uses 0 (or 7) for Sunday. As with cron,
+/// if both day-of-month and day-of-week are restricted, a day matching either is used.
+/// `@hourly`, `@daily`, `@weekly` and `@monthly` are accepted as shorthands.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Expected 5 fields in schedule '{}', found {}",
+                expression,
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // Fold 7 into 0 so that both can be used for Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+
+    /// The first time strictly after `after` which matches this schedule, in the same timezone.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let local = after.naive_local();
+        let start_date = local.date();
+
+        for offset in 0..=MAX_LOOKAHEAD_DAYS {
+            let date = start_date + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+
+            for hour in 0..24 {
+                if self.hours & (1 << hour) == 0 {
+                    continue;
+                }
+                for minute in 0..60 {
+                    if self.minutes & (1 << minute) == 0 {
+                        continue;
+                    }
+                    let Some(candidate) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    // Local times skipped by a DST transition don't exist and are ignored.
+                    if let Some(time) = tz.from_local_datetime(&candidate).earliest() {
+                        if time > *after {
+                            return Some(time);
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// Parse a single cron field into a bitmask where bit `n` is set if `n` matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid step '{}' in '{}'", step, field))?;
+                if step == 0 {
+                    return Err(format!("Step cannot be zero in '{}'", field));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, field)?, parse_value(end, field)?)
+        } else {
+            let value = parse_value(range, field)?;
+            // `5/10` means "from 5 to the end, every 10".
+            if step > 1 {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("Value out of range {}-{} in '{}'", min, max, field));
+        }
+
+        let mut value = start;
+        while value <= end {
+            mask |= 1 << value;
+            value += step;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(value: &str, field: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value '{}' in '{}'", value, field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDateTime, Utc};
+    use chrono_tz::Asia::Kolkata;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        Schedule::parse(expression)
+            .unwrap()
+            .next_after(&at(after))
+            .unwrap()
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    #[test]
+    fn parses_fields() {
+        let schedule = Schedule::parse("*/15 9-17 1,15 * 1-5").unwrap();
+        assert_eq!(
+            schedule.minutes,
+            (1 << 0) | (1 << 15) | (1 << 30) | (1 << 45)
+        );
+        assert_eq!(schedule.hours, (9..=17).map(|h| 1 << h).sum::<u64>());
+        assert_eq!(schedule.days_of_month, (1 << 1) | (1 << 15));
+        assert_eq!(schedule.months, (1..=12).map(|m| 1 << m).sum::<u64>());
+        assert_eq!(schedule.days_of_week, (1..=5).map(|d| 1 << d).sum::<u64>());
+        assert!(schedule.dom_restricted && schedule.dow_restricted);
+
+        assert_eq!(
+            Schedule::parse("0-30/10 * * * *").unwrap().minutes,
+            (1 << 0) | (1 << 10) | (1 << 20) | (1 << 30)
+        );
+        assert_eq!(
+            Schedule::parse("5/20 * * * *").unwrap().minutes,
+            (1 << 5) | (1 << 25) | (1 << 45)
+        );
+        assert_eq!(Schedule::parse("0 0 * * 7").unwrap().days_of_week, 1);
+        assert_eq!(Schedule::parse("@daily").unwrap().to_string(), "@daily");
+    }
+
+    #[test]
+    fn rejects_invalid_fields() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "*/x * * * *",
+            "a * * * *",
+            "1,,2 * * * *",
+            "@yearly",
+        ] {
+            assert!(
+                Schedule::parse(expression).is_err(),
+                "{:?} should be invalid",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn next_is_strictly_after() {
+        assert_eq!(next("30 0 * * *", "2026-01-10 00:29"), "2026-01-10 00:30");
+        assert_eq!(next("30 0 * * *", "2026-01-10 00:30"), "2026-01-11 00:30");
+        assert_eq!(next("*/15 * * * *", "2026-01-10 10:44"), "2026-01-10 10:45");
+        assert_eq!(
+            next("0 9-17/4 * * *", "2026-01-10 13:00"),
+            "2026-01-10 17:00"
+        );
+    }
+
+    #[test]
+    fn next_rolls_over_days_months_and_years() {
+        assert_eq!(next("0 0 * * *", "2026-01-31 23:59"), "2026-02-01 00:00");
+        assert_eq!(next("0 0 31 * *", "2026-01-31 12:00"), "2026-03-31 00:00");
+        assert_eq!(next("@monthly", "2026-12-15 00:00"), "2027-01-01 00:00");
+        assert_eq!(next("0 0 29 2 *", "2026-03-01 00:00"), "2028-02-29 00:00");
+        assert!(Schedule::parse("0 0 31 2 *")
+            .unwrap()
+            .next_after(&at("2026-01-01 00:00"))
+            .is_none());
+    }
+
+    #[test]
+    fn next_matches_either_day_field() {
+        // 2026-01-10 is a Saturday
+        assert_eq!(next("0 0 * * 1", "2026-01-10 00:00"), "2026-01-12 00:00");
+        assert_eq!(next("0 0 15 * 1", "2026-01-10 00:00"), "2026-01-12 00:00");
+        assert_eq!(next("0 0 11 * 1", "2026-01-10 00:00"), "2026-01-11 00:00");
+        assert_eq!(next("@weekly", "2026-01-10 00:00"), "2026-01-11 00:00");
+    }
+
+    #[test]
+    fn next_is_in_the_given_timezone() {
+        // 18:30 UTC is midnight IST
+        let after = at("2026-01-10 18:00").with_timezone(&Kolkata);
+        let next = Schedule::parse("0 0 * * *")
+            .unwrap()
+            .next_after(&after)
+            .unwrap();
+        assert_eq!(next.naive_local().to_string(), "2026-01-11 00:00:00");
+        assert_eq!(next.with_timezone(&Utc), at("2026-01-10 18:30"));
+    }
+}
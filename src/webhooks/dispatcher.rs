@@ -0,0 +1,177 @@
+use chrono::NaiveDateTime;
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use super::sign;
+use crate::models::webhook::WebhookEvent;
+
+/// How often pending deliveries are checked for when there's nothing to send
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Deliveries claimed at once
+const BATCH_SIZE: i64 = 20;
+
+/// How long a claimed delivery is protected from other instances, covering the request
+const CLAIM_DURATION: chrono::Duration = chrono::Duration::minutes(5);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before each retry. A delivery fails for good once these run out.
+const RETRY_DELAYS: [chrono::Duration; 5] = [
+    chrono::Duration::minutes(1),
+    chrono::Duration::minutes(5),
+    chrono::Duration::minutes(30),
+    chrono::Duration::hours(2),
+    chrono::Duration::hours(12),
+];
+
+#[derive(FromRow)]
+struct ClaimedDelivery {
+    delivery_id: i64,
+    event: WebhookEvent,
+    payload: Value,
+    attempts: i32,
+    created_at: NaiveDateTime,
+    url: String,
+    secret: String,
+}
+
+/// Sends queued webhook deliveries in the background, retrying failures with backoff. Each
+/// delivery is claimed before it's sent, so several instances can run dispatchers at once.
+pub struct WebhookDispatcher {
+    pool: Arc<PgPool>,
+    http: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client must build");
+
+        Self { pool, http }
+    }
+
+    pub fn start(self) {
+        info!("Starting webhook dispatcher");
+        tokio::spawn(async move {
+            loop {
+                match self.deliver_due().await {
+                    // A full batch means more deliveries are probably waiting
+                    Ok(sent) if sent as i64 == BATCH_SIZE => {}
+                    Ok(_) => tokio::time::sleep(POLL_INTERVAL).await,
+                    Err(e) => {
+                        error!("Failed to dispatch webhooks: {}", e);
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Claim and send a batch of due deliveries. Returns how many were sent.
+    async fn deliver_due(&self) -> Result<usize, String> {
+        let claimed = sqlx::query_as::<_, ClaimedDelivery>(
+            "UPDATE WebhookDelivery d
+            SET attempts = d.attempts + 1, next_attempt_at = NOW() + $1
+            FROM Webhook w
+            WHERE w.webhook_id = d.webhook_id
+            AND d.delivery_id IN (
+                SELECT delivery_id FROM WebhookDelivery
+                JOIN Webhook USING (webhook_id)
+                WHERE status = 'Pending' AND next_attempt_at <= NOW() AND is_active
+                ORDER BY next_attempt_at
+                LIMIT $2
+                FOR UPDATE OF WebhookDelivery SKIP LOCKED
+            )
+            RETURNING d.delivery_id, d.event, d.payload, d.attempts, d.created_at, w.url, w.secret",
+        )
+        .bind(CLAIM_DURATION)
+        .bind(BATCH_SIZE)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| format!("Failed to claim deliveries: {}", e))?;
+
+        let sent = claimed.len();
+        for result in join_all(claimed.into_iter().map(|delivery| self.deliver(delivery))).await {
+            if let Err(e) = result {
+                error!("{}", e);
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// POST a delivery to its webhook and record the outcome
+    async fn deliver(&self, delivery: ClaimedDelivery) -> Result<(), String> {
+        let body = json!({
+            "deliveryId": delivery.delivery_id,
+            "event": delivery.event.as_str(),
+            "createdAt": delivery.created_at,
+            "data": delivery.payload,
+        })
+        .to_string();
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let response = self
+            .http
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Root-Event", delivery.event.as_str())
+            .header("X-Root-Delivery", delivery.delivery_id.to_string())
+            .header("X-Root-Timestamp", timestamp.to_string())
+            .header(
+                "X-Root-Signature",
+                format!("sha256={}", sign(&delivery.secret, timestamp, &body)),
+            )
+            .body(body)
+            .send()
+            .await;
+
+        let (response_status, error) = match response {
+            Ok(response) if response.status().is_success() => {
+                (Some(response.status().as_u16() as i32), None)
+            }
+            Ok(response) => (
+                Some(response.status().as_u16() as i32),
+                Some(format!("Receiver answered {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let retry_delay = RETRY_DELAYS.get(delivery.attempts as usize - 1);
+        match (&error, retry_delay) {
+            (None, _) => debug!("Delivered webhook {}", delivery.delivery_id),
+            (Some(e), Some(_)) => debug!("Webhook {} failed: {}", delivery.delivery_id, e),
+            (Some(e), None) => warn!("Webhook {} failed for good: {}", delivery.delivery_id, e),
+        }
+
+        sqlx::query(
+            "UPDATE WebhookDelivery SET
+                status = CASE
+                    WHEN $2 IS NULL THEN 'Delivered'
+                    WHEN $4::INTERVAL IS NULL THEN 'Failed'
+                    ELSE 'Pending'
+                END::webhook_delivery_status,
+                delivered_at = CASE WHEN $2 IS NULL THEN NOW() END,
+                next_attempt_at = CASE WHEN $2 IS NOT NULL THEN NOW() + $4 END,
+                response_status = $1,
+                error = $2
+            WHERE delivery_id = $3",
+        )
+        .bind(response_status)
+        .bind(&error)
+        .bind(delivery.delivery_id)
+        .bind(retry_delay.copied())
+        .execute(self.pool.as_ref())
+        .await
+        .map_err(|e| format!("Failed to record webhook delivery: {}", e))?;
+
+        Ok(())
+    }
+}
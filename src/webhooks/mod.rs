@@ -0,0 +1,199 @@
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::{PgExecutor, PgPool, QueryBuilder};
+use tracing::warn;
+
+use crate::auth::session::SessionService;
+use crate::models::webhook::{
+    CreateWebhookInput, UpdateWebhookInput, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+    WebhookEvent, WebhookResponse,
+};
+
+pub mod dispatcher;
+pub mod streaks;
+
+pub use dispatcher::WebhookDispatcher;
+pub use streaks::StreakBrokenJob;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct WebhookService;
+
+impl WebhookService {
+    pub async fn list(pool: &PgPool) -> Result<Vec<Webhook>, String> {
+        sqlx::query_as::<_, Webhook>("SELECT * FROM Webhook ORDER BY webhook_id")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch webhooks: {}", e))
+    }
+
+    /// Register a webhook with a new signing secret
+    pub async fn create(
+        pool: &PgPool,
+        input: CreateWebhookInput,
+        created_by: Option<i32>,
+    ) -> Result<WebhookResponse, String> {
+        validate_url(&input.url)?;
+        validate_events(&input.events)?;
+        let secret = SessionService::generate_token();
+
+        let webhook = sqlx::query_as::<_, Webhook>(
+            "INSERT INTO Webhook (url, secret, events, description, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *",
+        )
+        .bind(&input.url)
+        .bind(&secret)
+        .bind(&input.events)
+        .bind(&input.description)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to create webhook: {}", e))?;
+
+        Ok(WebhookResponse { webhook, secret })
+    }
+
+    pub async fn update(
+        pool: &PgPool,
+        webhook_id: i32,
+        input: UpdateWebhookInput,
+    ) -> Result<Option<Webhook>, String> {
+        if let Some(url) = &input.url {
+            validate_url(url)?;
+        }
+        if let Some(events) = &input.events {
+            validate_events(events)?;
+        }
+
+        sqlx::query_as::<_, Webhook>(
+            "UPDATE Webhook SET
+                url = COALESCE($1, url),
+                events = COALESCE($2, events),
+                description = COALESCE($3, description),
+                is_active = COALESCE($4, is_active)
+            WHERE webhook_id = $5
+            RETURNING *",
+        )
+        .bind(&input.url)
+        .bind(&input.events)
+        .bind(&input.description)
+        .bind(input.is_active)
+        .bind(webhook_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to update webhook: {}", e))
+    }
+
+    /// Delete a webhook along with its delivery log
+    pub async fn delete(pool: &PgPool, webhook_id: i32) -> Result<bool, String> {
+        let result = sqlx::query("DELETE FROM Webhook WHERE webhook_id = $1")
+            .bind(webhook_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to delete webhook: {}", e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deliveries, newest first, optionally of one webhook or in one status
+    pub async fn deliveries(
+        pool: &PgPool,
+        webhook_id: Option<i32>,
+        status: Option<WebhookDeliveryStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDelivery>, String> {
+        let mut query = QueryBuilder::new("SELECT * FROM WebhookDelivery WHERE TRUE");
+        if let Some(webhook_id) = webhook_id {
+            query.push(" AND webhook_id = ").push_bind(webhook_id);
+        }
+        if let Some(status) = status {
+            query.push(" AND status = ").push_bind(status);
+        }
+        query
+            .push(" ORDER BY delivery_id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        query
+            .build_query_as::<WebhookDelivery>()
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch webhook deliveries: {}", e))
+    }
+
+    /// Queue a delivery of `event` to every active webhook subscribed to it. Returns how
+    /// many were queued. Takes a transaction to queue several events together.
+    pub async fn enqueue(
+        executor: impl PgExecutor<'_>,
+        event: WebhookEvent,
+        data: Value,
+    ) -> Result<u64, String> {
+        let result = sqlx::query(
+            "INSERT INTO WebhookDelivery (webhook_id, event, payload)
+            SELECT webhook_id, $1, $2 FROM Webhook
+            WHERE is_active AND $1 = ANY(events)",
+        )
+        .bind(event)
+        .bind(data)
+        .execute(executor)
+        .await
+        .map_err(|e| format!("Failed to queue {} webhooks: {}", event.as_str(), e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Like [`WebhookService::enqueue`], but only logs failures, for callers whose own work
+    /// has already succeeded
+    pub async fn try_enqueue(pool: &PgPool, event: WebhookEvent, data: Value) {
+        if let Err(e) = Self::enqueue(pool, event, data).await {
+            warn!("{}", e);
+        }
+    }
+
+    /// Queue a new delivery with the same payload as `delivery_id`, sent on the next poll
+    pub async fn redeliver(
+        pool: &PgPool,
+        delivery_id: i64,
+    ) -> Result<Option<WebhookDelivery>, String> {
+        sqlx::query_as::<_, WebhookDelivery>(
+            "INSERT INTO WebhookDelivery (webhook_id, event, payload, redelivery_of)
+            SELECT webhook_id, event, payload, delivery_id FROM WebhookDelivery
+            WHERE delivery_id = $1
+            RETURNING *",
+        )
+        .bind(delivery_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to redeliver webhook: {}", e))
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `{timestamp}.{body}`, sent as `X-Root-Signature`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        _ => Err("Webhook URLs must use http or https".to_string()),
+    }
+}
+
+fn validate_events(events: &[WebhookEvent]) -> Result<(), String> {
+    if events.is_empty() {
+        return Err("Webhooks must subscribe to at least one event".to_string());
+    }
+
+    Ok(())
+}
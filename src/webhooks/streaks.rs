@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate};
+use serde_json::json;
+use sqlx::{FromRow, PgPool};
+use tracing::info;
+
+use super::WebhookService;
+use crate::daily_task::today;
+use crate::models::webhook::WebhookEvent;
+use crate::scheduler::Job;
+
+#[derive(FromRow)]
+struct BrokenStreak {
+    member_id: i32,
+    streak: i64,
+    on_break: bool,
+}
+
+/// Sends `streak.broken` for members who sent a status update the day before yesterday but
+/// not yesterday. Runs late in the morning, once the status update bot has marked the
+/// previous night's updates.
+pub struct StreakBrokenJob;
+
+#[async_trait]
+impl Job for StreakBrokenJob {
+    fn name(&self) -> &'static str {
+        "streak_broken_webhooks"
+    }
+
+    fn schedule(&self) -> &'static str {
+        "0 10 * * *"
+    }
+
+    async fn run(&self, pool: &PgPool) -> Result<(), String> {
+        let date = today() - Duration::days(1);
+        let broken = broken_streaks(pool, date).await?;
+
+        // All or nothing, so that a retry doesn't queue any event twice
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        for streak in &broken {
+            WebhookService::enqueue(
+                &mut *tx,
+                WebhookEvent::StreakBroken,
+                json!({
+                    "memberId": streak.member_id,
+                    "date": date,
+                    "streak": streak.streak,
+                    "onBreak": streak.on_break,
+                }),
+            )
+            .await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit streak events: {}", e))?;
+
+        if !broken.is_empty() {
+            info!("{} status update streaks broke on {}", broken.len(), date);
+        }
+        Ok(())
+    }
+}
+
+/// Members who missed `date` after sending the day before, with the length of the streak
+/// that ended. Streaks don't skip breaks, so `on_break` tells whether `date` was in one.
+async fn broken_streaks(pool: &PgPool, date: NaiveDate) -> Result<Vec<BrokenStreak>, String> {
+    sqlx::query_as::<_, BrokenStreak>(
+        "SELECT missed.member_id, streak.streak,
+            EXISTS (
+                SELECT 1 FROM StatusBreaks sb
+                WHERE (sb.batch IS NULL OR sb.batch = m.batch)
+                AND $1 BETWEEN sb.start_date AND sb.end_date
+            ) AS on_break
+        FROM StatusUpdateHistory missed
+        JOIN StatusUpdateHistory previous
+            ON previous.member_id = missed.member_id
+            AND previous.date = missed.date - 1
+            AND previous.is_sent
+        JOIN Member m ON m.member_id = missed.member_id
+        CROSS JOIN LATERAL (
+            -- Consecutive dates share date - row number, see the streak field of Member
+            SELECT COUNT(*) AS streak
+            FROM (
+                SELECT date, date - (ROW_NUMBER() OVER (ORDER BY date))::INT AS island
+                FROM StatusUpdateHistory
+                WHERE member_id = missed.member_id AND is_sent AND date < $1
+            ) sent
+            GROUP BY island
+            ORDER BY MAX(date) DESC
+            LIMIT 1
+        ) streak
+        WHERE missed.date = $1 AND NOT missed.is_sent",
+    )
+    .bind(date)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to find broken streaks: {}", e))
+}